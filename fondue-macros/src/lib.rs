@@ -0,0 +1,124 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, FnArg, ItemFn, LitInt, LitStr, Pat};
+
+/// Memoization attribute for plain functions
+///
+/// Usage: `#[fondue::memoize]`, `#[fondue::memoize(ttl = "5m")]`,
+/// `#[fondue::memoize(limit = 100)]` or `#[fondue::memoize(ttl = "5m", limit = 100)]`
+///
+/// Wraps the function so each call goes through the global cache in a namespace
+/// named after the function's module path. Arguments form the key and must
+/// implement `ToString`; the return type must implement `ToString + FromStr`.
+/// Recursive calls go through the cache too, so `fn fib(n: u64) -> u64` is
+/// memoized at every level.
+#[proc_macro_attribute]
+pub fn memoize(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut ttl: Option<LitStr> = None;
+    let mut limit: Option<LitInt> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("ttl") {
+            ttl = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("limit") {
+            limit = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported memoize argument, expected `ttl` or `limit`"))
+        }
+    });
+    parse_macro_input!(attr with parser);
+    let function = parse_macro_input!(item as ItemFn);
+    expand(function, ttl, limit)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(
+    function: ItemFn,
+    ttl: Option<LitStr>,
+    limit: Option<LitInt>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = function;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new(
+            asyncness.span(),
+            "memoize does not support async functions",
+        ));
+    }
+
+    let mut arg_names = Vec::new();
+    for input in &sig.inputs {
+        match input {
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new(
+                    receiver.span(),
+                    "memoize only supports free functions",
+                ))
+            }
+            FnArg::Typed(typed) => match typed.pat.as_ref() {
+                Pat::Ident(pat) => arg_names.push(pat.ident.clone()),
+                other => {
+                    return Err(syn::Error::new(
+                        other.span(),
+                        "memoize arguments must be plain identifiers",
+                    ))
+                }
+            },
+        }
+    }
+
+    let name = &sig.ident;
+    let inner_name = format_ident!("__fondue_memoized_{}", name);
+    let mut inner_sig = sig.clone();
+    inner_sig.ident = inner_name.clone();
+    let namespace = quote! { concat!(module_path!(), "::", stringify!(#name)) };
+    let compute = quote! { || #inner_name(#(#arg_names),*) };
+    let ttl = ttl.map(|ttl| {
+        quote! {
+            ::fondue::parse_duration(#ttl)
+                .unwrap_or_else(|e| panic!("Invalid TTL {:?}: {}", #ttl, e))
+        }
+    });
+    let call = match (ttl, limit) {
+        (None, None) => quote! { ::fondue::cache_get(#namespace, &__key, #compute) },
+        (Some(ttl), None) => quote! {
+            ::fondue::cache_get_with_ttl(
+                #namespace,
+                &__key,
+                #ttl,
+                ::fondue::TtlType::Fixed,
+                #compute,
+            )
+        },
+        (None, Some(limit)) => quote! {
+            ::fondue::cache_get_with_limit(#namespace, &__key, #limit, #compute)
+        },
+        (Some(ttl), Some(limit)) => quote! {
+            ::fondue::cache_get_with_ttl_and_limit(
+                #namespace,
+                &__key,
+                #ttl,
+                #limit,
+                ::fondue::TtlType::Fixed,
+                #compute,
+            )
+        },
+    };
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            #inner_sig #block
+            let __key = ::fondue::__private::memoize_key(&[
+                #(::std::string::ToString::to_string(&#arg_names)),*
+            ]);
+            #call
+        }
+    })
+}
@@ -0,0 +1,38 @@
+//! Insert latency as a cache fills up.
+//!
+//! Run with `cargo bench --bench insert`. Each row is the mean insert time over
+//! one tenth of the fill; with a reaper the rows should stay flat, while
+//! `SweepOnWrite` grows with the number of stored entries.
+
+use fondue::{Cache, EvictionPolicy, ExpiryStrategy, TtlType};
+use std::time::{Duration, Instant};
+
+/// Inserts `entries` keys, printing the mean latency of each tenth
+fn fill(label: &str, expiry: ExpiryStrategy, entries: u64) {
+    let policy = EvictionPolicy::LruTtl {
+        limit: entries as usize,
+        duration: Duration::from_secs(600),
+        ttl_type: TtlType::Fixed,
+    };
+    let cache: Cache<u64, u64> = Cache::with_expiry(policy, expiry);
+    let chunk = entries / 10;
+    println!("{} ({} entries)", label, entries);
+    for decile in 0..10 {
+        let started = Instant::now();
+        for key in decile * chunk..(decile + 1) * chunk {
+            cache.insert(key, key);
+        }
+        let mean = started.elapsed() / chunk as u32;
+        println!("  {:>3}% full: {:>10?} per insert", (decile + 1) * 10, mean);
+    }
+}
+
+fn main() {
+    fill(
+        "Reaper(1s)",
+        ExpiryStrategy::Reaper(Duration::from_secs(1)),
+        200_000,
+    );
+    fill("OnAccessOnly", ExpiryStrategy::OnAccessOnly, 200_000);
+    fill("SweepOnWrite", ExpiryStrategy::SweepOnWrite, 10_000);
+}
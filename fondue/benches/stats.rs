@@ -0,0 +1,69 @@
+//! Stats publishing throughput as more caches publish at once.
+//!
+//! Run with `cargo bench --bench stats`. Every thread stands in for one cache,
+//! repeatedly publishing its reading under its own name. `GlobalStats` is
+//! compared with a single `Mutex<HashMap>`, the layout it used before. Each
+//! figure is wall-clock time divided by the total number of updates, so on a
+//! machine with spare cores the `GlobalStats` column falls as threads are
+//! added while the mutex column, serialized on one lock, does not.
+
+use fondue::{CacheStats, GlobalStats};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const UPDATES: u64 = 200_000;
+
+/// Runs `publish(thread, update)` on `threads` threads, returning wall time per update
+fn measure<F>(threads: usize, publish: F) -> Duration
+where
+    F: Fn(usize, u64) + Send + Sync + 'static,
+{
+    let publish = Arc::new(publish);
+    let started = Instant::now();
+    let workers: Vec<_> = (0..threads)
+        .map(|thread| {
+            let publish = Arc::clone(&publish);
+            thread::spawn(move || {
+                for update in 0..UPDATES {
+                    publish(thread, update);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    started.elapsed() / (UPDATES * threads as u64) as u32
+}
+
+/// A reading for cache `thread` after `update` requests
+fn reading(names: &[String], thread: usize, update: u64) -> CacheStats {
+    let mut stats = CacheStats::new(names[thread].clone());
+    stats.hits = update;
+    stats
+}
+
+fn main() {
+    let names: Arc<Vec<String>> = Arc::new((0..64).map(|i| format!("cache-{}", i)).collect());
+    println!("threads   GlobalStats   Mutex<HashMap>");
+    for threads in [1, 2, 4, 8, 16] {
+        let stats = GlobalStats::new();
+        let stats_names = Arc::clone(&names);
+        let sharded = measure(threads, move |thread, update| {
+            stats.update(&stats_names[thread], reading(&stats_names, thread, update));
+        });
+
+        let locked = Mutex::new(HashMap::new());
+        let locked_names = Arc::clone(&names);
+        let mutex = measure(threads, move |thread, update| {
+            let stats = reading(&locked_names, thread, update);
+            locked
+                .lock()
+                .unwrap()
+                .insert(locked_names[thread].clone(), stats);
+        });
+        println!("{:>7}   {:>11?}   {:>14?}", threads, sharded, mutex);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::cache::{Cache, CacheOutcome, EvictionPolicy};
+use crate::stats::CacheStats;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Cache handing out `Arc<V>` so hits never clone the value itself
+///
+/// Every hit returns a new pointer to the same allocation, which costs one
+/// reference-count increment regardless of the value's size. `V` need not be
+/// `Clone`, so values holding file handles or other unique resources can be
+/// cached without wrapping them by hand. Values are shared
+/// between all callers and must be treated as immutable: to change one,
+/// `insert` a replacement instead of mutating it through interior mutability,
+/// or readers holding the old `Arc` will observe the change.
+pub struct ArcCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    cache: Cache<K, Arc<V>>,
+}
+
+impl<K, V> ArcCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Creates an `ArcCache` with the given eviction policy
+    pub fn new(policy: EvictionPolicy) -> Self {
+        Self {
+            cache: Cache::with_policy(policy),
+        }
+    }
+
+    /// Wraps an existing cache of shared values, keeping its configuration
+    ///
+    /// Use this to combine shared values with the other `Cache` constructors,
+    /// e.g. `ArcCache::from_cache(Cache::with_expiry(policy, expiry))`.
+    pub fn from_cache(cache: Cache<K, Arc<V>>) -> Self {
+        Self { cache }
+    }
+
+    /// Retrieves a shared pointer to the cached value, computing and caching it on a miss
+    pub fn get<F>(&self, key: &K, compute: F) -> Arc<V>
+    where
+        F: FnOnce() -> V,
+    {
+        self.cache.get(key, || Arc::new(compute()))
+    }
+
+    /// Fallible variant of `get`; errors are returned and nothing is cached
+    pub fn get_or_try<F, E>(&self, key: &K, compute: F) -> Result<Arc<V>, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        self.cache.get_or_try(key, || compute().map(Arc::new))
+    }
+
+    /// `get` that also reports whether the value was cached
+    pub fn get_tracked<F>(&self, key: &K, compute: F) -> (Arc<V>, CacheOutcome)
+    where
+        F: FnOnce() -> V,
+    {
+        self.cache.get_tracked(key, || Arc::new(compute()))
+    }
+
+    /// Looks up a key without computing
+    pub fn get_if_cached(&self, key: &K) -> Option<Arc<V>> {
+        self.cache.get_if_cached(key)
+    }
+
+    /// Returns a live entry's value without touching it or counting a hit
+    pub fn peek(&self, key: &K) -> Option<Arc<V>> {
+        self.cache.peek(key)
+    }
+
+    /// Checks whether a live entry exists for `key`
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.cache.contains_key(key)
+    }
+
+    /// Caches `value`, returning the live value it replaced
+    pub fn insert(&self, key: K, value: V) -> Option<Arc<V>> {
+        self.cache.insert(key, Arc::new(value))
+    }
+
+    /// Caches an already shared value, returning the live value it replaced
+    pub fn insert_arc(&self, key: K, value: Arc<V>) -> Option<Arc<V>> {
+        self.cache.insert(key, value)
+    }
+
+    /// Removes a key from the cache
+    pub fn invalidate(&self, key: &K) -> bool {
+        self.cache.invalidate(key)
+    }
+
+    /// Removes a key from the cache, returning its value if it was live
+    pub fn invalidate_take(&self, key: &K) -> Option<Arc<V>> {
+        self.cache.invalidate_take(key)
+    }
+
+    /// Removes every entry
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+
+    /// Returns the number of entries, including expired ones not yet removed
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Checks whether the cache holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Returns this cache's current statistics
+    pub fn stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Returns the underlying cache of shared values
+    pub fn cache(&self) -> &Cache<K, Arc<V>> {
+        &self.cache
+    }
+}
+
+impl<K, V> From<Cache<K, Arc<V>>> for ArcCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    fn from(cache: Cache<K, Arc<V>>) -> Self {
+        Self::from_cache(cache)
+    }
+}
+
+impl<K, V> Clone for ArcCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hits_share_one_allocation() {
+        // Vec<u8> clones are O(len); hits must not clone it
+        let cache: ArcCache<&str, Vec<u8>> = ArcCache::new(EvictionPolicy::Lru(4));
+        let first = cache.get(&"blob", || vec![7; 1 << 16]);
+        let second = cache.get(&"blob", || unreachable!());
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(Arc::strong_count(&first), 3);
+    }
+
+    #[test]
+    fn test_values_need_not_be_clone() {
+        struct Handle(u32); // Stands in for a file handle or connection
+
+        let cache: ArcCache<u32, Handle> =
+            ArcCache::from_cache(Cache::with_policy(EvictionPolicy::Lru(2)));
+        let (handle, outcome) = cache.get_tracked(&1, || Handle(7));
+        assert_eq!((handle.0, outcome), (7, CacheOutcome::Miss));
+        assert_eq!(cache.peek(&1).map(|handle| handle.0), Some(7));
+    }
+}
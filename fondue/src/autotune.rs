@@ -0,0 +1,189 @@
+use crate::cache::{Cache, EvictionPolicy};
+use crate::window;
+use std::hash::Hash;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// Bounds and goal for `Cache::enable_autotune`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutotuneConfig {
+    pub min: usize,           // Smallest LRU limit the tuner will set
+    pub max: usize,           // Largest LRU limit the tuner will set
+    pub target_hit_rate: f64, // Hit rate (0.0..=1.0) the tuner aims for
+    pub interval: Duration,   // How often the limit is adjusted, judged on that interval's requests
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Spawns a thread that adjusts the LRU limit every `config.interval`
+    ///
+    /// The limit grows by a quarter when the cache is full and the hit rate over
+    /// the last interval is below target, and shrinks by a quarter (never below
+    /// the current size) when the hit rate is above target with room to spare.
+    /// Intervals without requests, and caches without an LRU limit, are left
+    /// alone. Calling this again replaces the running tuner; the thread stops
+    /// on `disable_autotune` or once every handle to the cache is dropped.
+    pub fn enable_autotune(&self, config: AutotuneConfig) {
+        let generation = self.tuner_generation().fetch_add(1, Ordering::SeqCst) + 1;
+        let tuner = self.downgrade();
+        let mut seen = (self.hit_count(), self.miss_count());
+        std::thread::spawn(move || loop {
+            std::thread::sleep(config.interval);
+            let Some(cache) = tuner.upgrade() else {
+                break;
+            };
+            if cache.tuner_generation().load(Ordering::SeqCst) != generation {
+                break;
+            }
+            // Counted since the last tick, so each request informs one decision
+            let totals = (cache.hit_count(), cache.miss_count());
+            let (hits, misses) = (
+                totals.0.saturating_sub(seen.0),
+                totals.1.saturating_sub(seen.1),
+            );
+            seen = totals;
+            if hits + misses == 0 {
+                continue;
+            }
+            let policy = cache.policy();
+            let limit = match policy {
+                EvictionPolicy::Lru(limit)
+                | EvictionPolicy::LruTtl { limit, .. }
+                | EvictionPolicy::TinyLfu { limit }
+                | EvictionPolicy::SampledLru { limit, .. } => limit,
+                _ => continue,
+            };
+            let tuned = tuned_limit(limit, cache.len(), window::rate(hits, misses), &config);
+            if tuned != limit {
+                cache.set_policy(with_limit(policy, tuned));
+            }
+        });
+    }
+
+    /// Stops the tuner started by `enable_autotune`, leaving the limit where it is
+    pub fn disable_autotune(&self) {
+        self.tuner_generation().fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Picks the next LRU limit for a cache holding `len` entries at `hit_rate`
+fn tuned_limit(limit: usize, len: usize, hit_rate: f64, config: &AutotuneConfig) -> usize {
+    let step = (limit / 4).max(1);
+    let tuned = if hit_rate < config.target_hit_rate && len >= limit {
+        limit.saturating_add(step)
+    } else if hit_rate > config.target_hit_rate && len < limit {
+        limit.saturating_sub(step).max(len)
+    } else {
+        limit
+    };
+    tuned.clamp(config.min, config.max.max(config.min))
+}
+
+/// Returns `policy` with its LRU limit replaced by `limit`
+fn with_limit(policy: EvictionPolicy, limit: usize) -> EvictionPolicy {
+    match policy {
+        EvictionPolicy::LruTtl {
+            duration, ttl_type, ..
+        } => EvictionPolicy::LruTtl {
+            limit,
+            duration,
+            ttl_type,
+        },
+        EvictionPolicy::TinyLfu { .. } => EvictionPolicy::TinyLfu { limit },
+        EvictionPolicy::SampledLru { sample_size, .. } => {
+            EvictionPolicy::SampledLru { limit, sample_size }
+        }
+        _ => EvictionPolicy::Lru(limit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuned_limit_moves_within_bounds() {
+        let config = AutotuneConfig {
+            min: 10,
+            max: 100,
+            target_hit_rate: 0.8,
+            interval: Duration::from_secs(1),
+        };
+        // Full and missing: grow
+        assert_eq!(tuned_limit(40, 40, 0.5, &config), 50);
+        assert_eq!(tuned_limit(90, 90, 0.5, &config), 100);
+        // Spare capacity and hitting: shrink, but not below len or min
+        assert_eq!(tuned_limit(40, 20, 0.95, &config), 30);
+        assert_eq!(tuned_limit(40, 35, 0.95, &config), 35);
+        assert_eq!(tuned_limit(12, 2, 0.95, &config), 10);
+        // Otherwise unchanged
+        assert_eq!(tuned_limit(40, 20, 0.5, &config), 40);
+    }
+
+    #[test]
+    fn test_autotune_steps_once_per_burst_with_short_interval() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::Lru(40));
+        cache.enable_autotune(AutotuneConfig {
+            min: 10,
+            max: 1_000,
+            target_hit_rate: 0.8,
+            interval: Duration::from_millis(10),
+        });
+        for key in 0..100 {
+            cache.get(&key, || key);
+        }
+        std::thread::sleep(Duration::from_millis(200));
+        // One burst of misses grows the limit a step or two, not all the way to `max`
+        let limit = cache.capacity().unwrap();
+        assert!((50..100).contains(&limit), "limit {limit}");
+        cache.disable_autotune();
+    }
+
+    #[test]
+    fn test_enable_autotune_replaces_running_tuner() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::Lru(40));
+        let pinned_to = |limit| AutotuneConfig {
+            min: limit,
+            max: limit,
+            target_hit_rate: 0.8,
+            interval: Duration::from_millis(5),
+        };
+        cache.enable_autotune(pinned_to(20));
+        cache.enable_autotune(pinned_to(30));
+        for round in 0..20 {
+            cache.get(&round, || round);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(cache.capacity(), Some(30));
+        cache.disable_autotune();
+        // Let a tick already past its generation check finish
+        std::thread::sleep(Duration::from_millis(20));
+        cache.set_policy(EvictionPolicy::Lru(40));
+        for round in 0..5 {
+            cache.get(&round, || round);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(cache.capacity(), Some(40));
+    }
+
+    #[test]
+    fn test_autotune_and_reaper_stop_when_cache_dropped() {
+        let cache: Cache<u32, u32> = Cache::builder()
+            .policy(EvictionPolicy::Lru(10))
+            .expiry(crate::ExpiryStrategy::Reaper(Duration::from_millis(10)))
+            .build();
+        cache.enable_autotune(AutotuneConfig {
+            min: 5,
+            max: 20,
+            target_hit_rate: 0.8,
+            interval: Duration::from_millis(10),
+        });
+        let weak = cache.downgrade();
+        drop(cache);
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(weak.upgrade().is_none());
+    }
+}
@@ -0,0 +1,266 @@
+#[cfg(feature = "tracing")]
+use crate::cache::KeyFormatter;
+use crate::cache::{
+    Cache, ComputePolicy, EvictionListener, EvictionPolicy, ExpiryStrategy, Weigher,
+};
+use crate::clock::{Clock, SystemClock};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Fluent configuration for a `Cache`, combining options the `with_*` constructors set one at a time
+///
+/// ```
+/// use fondue::{Cache, EvictionPolicy, ExpiryStrategy, SystemClock};
+/// use std::time::Duration;
+///
+/// let cache: Cache<String, Vec<u8>> = Cache::builder()
+///     .policy(EvictionPolicy::Lru(1_000))
+///     .name("thumbnails")
+///     .weigher(|key: &String, value: &Vec<u8>| (key.len() + value.len()) as u64)
+///     .watermark(0.9)
+///     .expiry(ExpiryStrategy::Reaper(Duration::from_secs(30)))
+///     .on_evict(|key: &String, _: &Vec<u8>| println!("evicted {}", key))
+///     .clock(SystemClock)
+///     .build();
+/// assert_eq!(cache.capacity(), Some(1_000));
+/// ```
+pub struct CacheBuilder<K, V, S = RandomState> {
+    pub(crate) policy: EvictionPolicy,
+    pub(crate) name: Option<String>,
+    pub(crate) hasher: S,
+    pub(crate) shards: Option<usize>,
+    pub(crate) initial_capacity: usize,
+    pub(crate) jitter: f64,
+    pub(crate) low_watermark: f64,
+    pub(crate) expiry: ExpiryStrategy,
+    pub(crate) compute_policy: ComputePolicy,
+    pub(crate) weigher: Option<Weigher<K, V>>,
+    pub(crate) stale_if_error: Option<Duration>,
+    pub(crate) on_evict: Option<EvictionListener<K, V>>,
+    pub(crate) clock: Arc<dyn Clock>,
+    #[cfg(feature = "tracing")]
+    pub(crate) trace_keys: Option<KeyFormatter<K>>,
+}
+
+impl<K, V> CacheBuilder<K, V> {
+    /// Starts from the defaults of `Cache::new`
+    pub fn new() -> Self {
+        Self {
+            policy: EvictionPolicy::None,
+            name: None,
+            hasher: RandomState::new(),
+            shards: None,
+            initial_capacity: 0,
+            jitter: 0.0,
+            low_watermark: 1.0,
+            expiry: ExpiryStrategy::default(),
+            compute_policy: ComputePolicy::default(),
+            weigher: None,
+            stale_if_error: None,
+            on_evict: None,
+            clock: Arc::new(SystemClock),
+            #[cfg(feature = "tracing")]
+            trace_keys: None,
+        }
+    }
+}
+
+impl<K, V> Default for CacheBuilder<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, S> CacheBuilder<K, V, S> {
+    /// Sets the eviction policy
+    pub fn policy(mut self, policy: EvictionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Registers stats under `name` instead of an address-derived one
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Uses `hasher` for keys; see `Cache::with_hasher`
+    pub fn hasher<H>(self, hasher: H) -> CacheBuilder<K, V, H> {
+        CacheBuilder {
+            policy: self.policy,
+            name: self.name,
+            hasher,
+            shards: self.shards,
+            initial_capacity: self.initial_capacity,
+            jitter: self.jitter,
+            low_watermark: self.low_watermark,
+            expiry: self.expiry,
+            compute_policy: self.compute_policy,
+            weigher: self.weigher,
+            stale_if_error: self.stale_if_error,
+            on_evict: self.on_evict,
+            clock: self.clock,
+            #[cfg(feature = "tracing")]
+            trace_keys: self.trace_keys,
+        }
+    }
+
+    /// Splits storage into `shard_amount` shards; see `Cache::with_shards`
+    pub fn shards(mut self, shard_amount: usize) -> Self {
+        self.shards = Some(shard_amount);
+        self
+    }
+
+    /// Preallocates room for `capacity` entries; see `Cache::with_capacity`
+    pub fn initial_capacity(mut self, capacity: usize) -> Self {
+        self.initial_capacity = capacity;
+        self
+    }
+
+    /// Randomizes TTLs by up to this fraction; see `Cache::with_policy_jitter`
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Evicts down to this fraction of the limit; see `Cache::with_watermark`
+    pub fn watermark(mut self, low_watermark: f64) -> Self {
+        self.low_watermark = low_watermark;
+        self
+    }
+
+    /// Sets when expired entries are removed; see `Cache::with_expiry`
+    pub fn expiry(mut self, expiry: ExpiryStrategy) -> Self {
+        self.expiry = expiry;
+        self
+    }
+
+    /// Sets how concurrent misses compute; see `Cache::with_compute_policy`
+    pub fn compute_policy(mut self, compute: ComputePolicy) -> Self {
+        self.compute_policy = compute;
+        self
+    }
+
+    /// Sizes entries for `approx_memory_bytes`; see `Cache::with_weigher`
+    pub fn weigher<W>(mut self, weigher: W) -> Self
+    where
+        W: Fn(&K, &V) -> u64 + Send + Sync + 'static,
+    {
+        self.weigher = Some(Arc::new(weigher));
+        self
+    }
+
+    /// Serves expired values when recomputing fails; see `Cache::with_stale_if_error`
+    pub fn stale_if_error(mut self, max_stale: Duration) -> Self {
+        self.stale_if_error = Some(max_stale);
+        self
+    }
+
+    /// Calls `listener` with each entry removed by LRU eviction or expiry
+    ///
+    /// Explicit removals (`invalidate`, `clear`, `retain`, ...) are not reported.
+    /// The listener runs on the thread that triggered the removal, so keep it short.
+    pub fn on_evict<F>(mut self, listener: F) -> Self
+    where
+        F: Fn(&K, &V) + Send + Sync + 'static,
+    {
+        self.on_evict = Some(Arc::new(listener));
+        self
+    }
+
+    /// Sets the clock entries are timestamped with and TTLs measured against
+    ///
+    /// Defaults to `SystemClock`; pass a `ManualClock` to test expiry without sleeping.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Shows keys in `tracing` events as `format` renders them
+    ///
+    /// Keys are logged as `<redacted>` by default, since they may hold user data.
+    #[cfg(feature = "tracing")]
+    pub fn trace_keys<F>(mut self, format: F) -> Self
+    where
+        F: Fn(&K) -> String + Send + Sync + 'static,
+    {
+        self.trace_keys = Some(Arc::new(format));
+        self
+    }
+}
+
+impl<K, V, S> CacheBuilder<K, V, S>
+where
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+    S: BuildHasher + Clone + Send + Sync + 'static,
+{
+    /// Creates the cache, starting its reaper thread if one was configured
+    pub fn build(self) -> Cache<K, V, S> {
+        Cache::from_builder(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::stats::get_stats;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_builder_combines_options() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&evicted);
+        let cache: Cache<u32, u32> = Cache::builder()
+            .policy(EvictionPolicy::Lru(2))
+            .name("builder_test")
+            .shards(4)
+            .weigher(|_, _| 10)
+            .on_evict(move |key, _| sink.lock().unwrap().push(*key))
+            .build();
+        cache.insert(1, 1);
+        cache.insert(2, 2);
+        cache.insert(3, 3);
+        assert_eq!(*evicted.lock().unwrap(), [1]);
+        assert_eq!(cache.shard_count(), 4);
+        assert_eq!(cache.approx_memory_bytes(), 20);
+        assert_eq!(
+            get_stats("builder_test").map(|stats| stats.entries),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_clock_drives_expiry() {
+        let clock = ManualClock::new();
+        let cache: Cache<u32, u32> = Cache::builder()
+            .policy(EvictionPolicy::Ttl {
+                duration: Duration::from_secs(60),
+                ttl_type: crate::TtlType::Fixed,
+            })
+            .clock(clock.clone())
+            .build();
+        cache.insert(1, 1);
+        clock.advance(Duration::from_secs(59));
+        assert_eq!(cache.get_if_cached(&1), Some(1));
+        assert_eq!(cache.entry_age(&1), Some(Duration::from_secs(59)));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.get_if_cached(&1), None);
+    }
+
+    #[test]
+    fn test_new_cache_reusing_a_name_replaces_its_stats() {
+        let old: Cache<u32, u32> = Cache::builder().name("builder_reused").build();
+        for key in 0..10 {
+            old.get(&key, || key);
+        }
+        drop(old);
+        let new: Cache<u32, u32> = Cache::builder().name("builder_reused").build();
+        new.get(&0, || 0);
+        let stats = get_stats("builder_reused").unwrap();
+        assert_eq!((stats.entries, stats.misses), (1, 1));
+    }
+}
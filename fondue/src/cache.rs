@@ -1,9 +1,21 @@
-use crate::stats::{register_stats, CacheStats};
+use crate::builder::CacheBuilder;
+use crate::clock::{Clock, SystemClock};
+use crate::error::CacheError;
+use crate::latency::LatencyRecorder;
+use crate::registry::CacheRegistry;
+use crate::sketch::FrequencySketch;
+use crate::stats::{get_global_stats, record_stats, CacheStats, LatencyStats};
+use crate::window::{self, HitWindow};
 use dashmap::DashMap;
+#[cfg(feature = "async")]
+use std::future::Future;
 use std::{
-    hash::Hash,
-    sync::atomic::Ordering,
-    sync::{Arc, OnceLock},
+    cell::RefCell,
+    collections::hash_map::RandomState,
+    collections::{HashMap, HashSet},
+    hash::{BuildHasher, Hash},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    sync::{Arc, Mutex, OnceLock, PoisonError, RwLock, Weak},
     time::{Duration, Instant},
 };
 
@@ -14,6 +26,15 @@ pub enum TtlType {
     Sliding, // TTL counted from last accessed time
 }
 
+/// What the String-backed API does when a cached value won't parse as the requested type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseErrorPolicy {
+    #[default]
+    Panic, // Panic, as `cache_get` does
+    Recompute,     // Treat the entry as a miss: run the closure and replace it
+    ReturnDefault, // Return `V::default()` and leave the entry in place
+}
+
 /// Eviction policies supported by the cache
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum EvictionPolicy {
@@ -28,6 +49,44 @@ pub enum EvictionPolicy {
         duration: Duration,
         ttl_type: TtlType,
     }, // Combined LRU + TTL eviction
+    TinyLfu {
+        limit: usize,
+    }, // LRU eviction, but a computed key is only admitted if used more often than the LRU victim; inserts always store
+    SampledLru {
+        limit: usize,
+        sample_size: usize,
+    }, // Approximate LRU: evicts the least recently used of a random sample of entries
+    Disabled, // Nothing is stored: every get computes and counts a miss
+}
+
+/// How concurrent misses on the same key are computed
+///
+/// `get_many` is exempt: it computes its whole batch in one call without the
+/// per-key locks, so under `Serialized` it may still duplicate a concurrent
+/// `get` for the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ComputePolicy {
+    #[default]
+    Concurrent, // Each missing caller computes; no waiting, but work may be duplicated
+    Serialized, // Callers missing on the same key wait on a per-key lock; one computes
+}
+
+/// How a `get_tracked` call obtained its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheOutcome {
+    Hit,       // Served from the cache
+    Miss,      // Computed because the key was absent or expired
+    Refreshed, // Served from the cache and a refresh-ahead recomputation was started
+    Stale,     // Recomputing failed, so an expired value was served instead
+}
+
+/// When expired entries are removed from storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ExpiryStrategy {
+    OnAccessOnly, // Removed only when a read finds them expired
+    #[default]
+    SweepOnWrite, // Every write also sweeps the whole map (O(n) per write)
+    Reaper(Duration), // A background thread sweeps at this interval
 }
 
 /// Represents a cached entry with timing and access metadata
@@ -39,410 +98,4001 @@ pub struct CacheEntry<V> {
     pub access_count: u64,
     pub ttl: Option<Duration>,
     pub ttl_type: Option<TtlType>,
+    pub metadata: Option<Box<HashMap<String, String>>>, // User tags; boxed so untagged entries stay small
+    pub version: Option<u64>, // Token passed to `get_versioned`; a different one makes the entry stale
 }
 
 impl<V> CacheEntry<V> {
     /// Creates a new cache entry with current timestamps
     pub fn new(value: V, ttl: Option<Duration>, ttl_type: Option<TtlType>) -> Self {
-        let now = Instant::now();
-        Self {
-            value,
-            created_at: now,
-            last_accessed: now,
-            access_count: 0,
-            ttl,
-            ttl_type,
-        }
+        Self::new_at(value, ttl, ttl_type, Instant::now())
     }
 
     /// Checks if the entry is expired based on TTL and TTL type
     pub fn is_expired(&self) -> bool {
-        match (self.ttl, self.ttl_type.as_ref()) {
-            (Some(ttl), Some(ttl_type)) => match ttl_type {
-                TtlType::Sliding => self.last_accessed.elapsed() >= ttl,
-                TtlType::Fixed => self.created_at.elapsed() >= ttl,
-            },
-            _ => false,
-        }
+        self.is_expired_at(Instant::now())
     }
 
-    /// Updates last accessed time and increments access count
-    pub fn touch(&mut self) {
-        self.access_count += 1;
-        self.last_accessed = Instant::now();
+    /// Returns the time left before the entry expires, or `None` if it has no TTL
+    pub fn ttl_remaining(&self) -> Option<Duration> {
+        self.ttl_remaining_at(Instant::now())
     }
-}
 
-/// Generic cache supporting configurable eviction policies and TTL
-pub struct Cache<K, V>
-where
-    K: Hash + Eq + Clone,
-    V: Clone,
-{
-    storage: Arc<DashMap<K, CacheEntry<V>>>,
-    policy: EvictionPolicy,
-    hits: Arc<std::sync::atomic::AtomicU64>,
-    misses: Arc<std::sync::atomic::AtomicU64>,
-}
+    /// Returns how long ago the entry expired, or `None` if it has not or has no TTL
+    pub fn overdue(&self) -> Option<Duration> {
+        self.overdue_at(Instant::now())
+    }
 
-impl<K, V> Cache<K, V>
-where
-    K: Hash + Eq + Clone,
-    V: Clone,
-{
-    /// Creates a new cache without eviction policy
-    pub fn new() -> Self {
-        Self::with_policy(EvictionPolicy::None)
+    /// Updates last accessed time and increments access count
+    pub fn touch(&mut self) {
+        self.touch_at(Instant::now());
     }
 
-    /// Creates a new cache with specified eviction policy
-    pub fn with_policy(policy: EvictionPolicy) -> Self {
+    /// `new`, stamped with `now` from the cache's clock
+    pub(crate) fn new_at(
+        value: V,
+        ttl: Option<Duration>,
+        ttl_type: Option<TtlType>,
+        now: Instant,
+    ) -> Self {
         Self {
-            storage: Arc::new(DashMap::new()),
-            policy,
-            hits: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-            misses: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            value,
+            created_at: now,
+            last_accessed: now,
+            access_count: 0,
+            ttl,
+            ttl_type,
+            metadata: None,
+            version: None,
         }
     }
 
-    /// Retrieves cached value or computes and caches it
-    ///
-    /// Updates statistics after access and maintains eviction as needed.
-    pub fn get<F>(&self, key: &K, compute: F) -> V
-    where
-        F: FnOnce() -> V,
-    {
-        if let Some(entry) = self.storage.get(key) {
-            if !entry.is_expired() {
-                drop(entry);
-                if let Some(mut entry_mut) = self.storage.get_mut(key) {
-                    entry_mut.touch();
-                    self.hits.fetch_add(1, Ordering::Relaxed);
-                    self.update_cache_stats();
-                    return entry_mut.value.clone();
-                }
-            } else {
-                drop(entry);
-                self.storage.remove(key);
-                self.update_cache_stats();
-            }
-        }
-        self.misses.fetch_add(1, Ordering::Relaxed);
-        let (ttl, ttl_type) = match &self.policy {
-            EvictionPolicy::Ttl { duration, ttl_type } => (Some(*duration), Some(ttl_type.clone())),
-            EvictionPolicy::LruTtl {
-                duration, ttl_type, ..
-            } => (Some(*duration), Some(ttl_type.clone())),
-            _ => (None, None),
-        };
-        let value = compute();
-        let entry = CacheEntry::new(value.clone(), ttl, ttl_type);
-        self.storage.insert(key.clone(), entry);
-        self.maybe_evict();
-        self.update_cache_stats();
-        value
+    /// `is_expired` as of `now`
+    pub(crate) fn is_expired_at(&self, now: Instant) -> bool {
+        self.ttl_progress(now)
+            .is_some_and(|(ttl, elapsed)| elapsed >= ttl)
     }
 
-    /// Attempts to retrieve cached value without computing
-    pub fn get_if_cached(&self, key: &K) -> Option<V> {
-        if let Some(mut entry) = self.storage.get_mut(key) {
-            if !entry.is_expired() {
-                entry.touch();
-                self.hits.fetch_add(1, Ordering::Relaxed);
-                self.update_cache_stats();
-                return Some(entry.value.clone());
-            } else {
-                drop(entry);
-                self.storage.remove(key);
-                self.update_cache_stats();
-            }
-        }
-        None
+    /// `ttl_remaining` as of `now`
+    pub(crate) fn ttl_remaining_at(&self, now: Instant) -> Option<Duration> {
+        self.ttl_progress(now)
+            .map(|(ttl, elapsed)| ttl.saturating_sub(elapsed))
     }
 
-    /// Inserts a value directly into the cache
-    pub fn insert(&self, key: K, value: V) {
-        let (ttl, ttl_type) = match &self.policy {
-            EvictionPolicy::Ttl { duration, ttl_type } => (Some(*duration), Some(ttl_type.clone())),
-            EvictionPolicy::LruTtl {
-                duration, ttl_type, ..
-            } => (Some(*duration), Some(ttl_type.clone())),
-            _ => (None, None),
-        };
-        let entry = CacheEntry::new(value, ttl, ttl_type);
-        self.storage.insert(key, entry);
-        self.maybe_evict();
-        self.update_cache_stats();
+    /// `overdue` as of `now`
+    pub(crate) fn overdue_at(&self, now: Instant) -> Option<Duration> {
+        self.ttl_progress(now)
+            .and_then(|(ttl, elapsed)| elapsed.checked_sub(ttl))
     }
 
-    /// Removes an entry by key, returns true if found and removed
-    pub fn invalidate(&self, key: &K) -> bool {
-        let removed = self.storage.remove(key).is_some();
-        if removed {
-            self.update_cache_stats();
-        }
-        removed
+    /// `touch`, stamped with `now`
+    pub(crate) fn touch_at(&mut self, now: Instant) {
+        self.access_count += 1;
+        self.last_accessed = now;
     }
 
-    /// Clears all entries in the cache
-    pub fn clear(&self) {
-        self.storage.clear();
-        self.update_cache_stats();
+    /// Returns the TTL and how much of it has run by `now`, or `None` without a TTL
+    fn ttl_progress(&self, now: Instant) -> Option<(Duration, Duration)> {
+        let ttl = self.ttl?;
+        let since = match self.ttl_type.as_ref()? {
+            TtlType::Sliding => self.last_accessed,
+            TtlType::Fixed => self.created_at,
+        };
+        Some((ttl, now.saturating_duration_since(since)))
     }
+}
 
-    /// Returns current number of entries in the cache
-    pub fn len(&self) -> usize {
-        self.storage.len()
-    }
+/// Outcome of a batched lookup, telling cached values apart from freshly computed ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched<V> {
+    Hit(V),      // Value was already cached
+    Computed(V), // Value was produced by the compute closure
+}
 
-    /// Checks whether the cache is empty
-    pub fn is_empty(&self) -> bool {
-        self.storage.is_empty()
-    }
+/// Lock-free handle to a cache's hit and miss counters for external metrics
+///
+/// Shares the cache's own atomics, so it keeps reporting live values for as
+/// long as it is held, including after the cache or its clones are dropped.
+#[derive(Debug, Clone)]
+pub struct CacheCounters {
+    hits: Arc<std::sync::atomic::AtomicU64>,
+    misses: Arc<std::sync::atomic::AtomicU64>,
+}
 
-    /// Returns number of cache hits
-    pub fn hit_count(&self) -> u64 {
+impl CacheCounters {
+    /// Returns the number of hits so far
+    pub fn hits(&self) -> u64 {
         self.hits.load(Ordering::Relaxed)
     }
 
-    /// Returns number of cache misses
-    pub fn miss_count(&self) -> u64 {
+    /// Returns the number of misses so far
+    pub fn misses(&self) -> u64 {
         self.misses.load(Ordering::Relaxed)
     }
 
-    /// Calculates current hit rate as fraction in [0.0, 1.0]
+    /// Returns the lifetime hit rate as a fraction in [0.0, 1.0]
     pub fn hit_rate(&self) -> f64 {
-        let hits = self.hit_count();
-        let total = hits + self.miss_count();
-        if total == 0 {
-            0.0
-        } else {
-            hits as f64 / total as f64
-        }
+        window::rate(self.hits(), self.misses())
     }
+}
 
-    /// Removes expired entries and evicts based on policy limits if needed
-    fn maybe_evict(&self) {
-        let keys_to_remove: Vec<_> = self
-            .storage
-            .iter()
-            .filter_map(|entry| {
-                if entry.value().is_expired() {
-                    Some(entry.key().clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
-        for key in keys_to_remove {
-            self.storage.remove(&key);
-        }
-        match &self.policy {
-            EvictionPolicy::Lru(limit) | EvictionPolicy::LruTtl { limit, .. } => {
-                if self.storage.len() > *limit {
-                    self.evict_lru(self.storage.len() - limit);
-                }
-            }
-            _ => {}
-        }
+/// Stops a reaper started by `Cache::spawn_async_reaper`
+///
+/// Dropping it leaves the reaper running.
+#[cfg(feature = "async")]
+#[derive(Debug, Clone)]
+pub struct ReaperStop {
+    notify: Arc<tokio::sync::Notify>,
+}
+
+#[cfg(feature = "async")]
+impl ReaperStop {
+    /// Ends the reaper task before its next sweep
+    pub fn stop(&self) {
+        self.notify.notify_one();
     }
+}
 
-    /// Evicts least recently used entries equal to `count`
-    fn evict_lru(&self, count: usize) {
-        let mut entries: Vec<_> = self
-            .storage
-            .iter()
-            .map(|entry| {
-                (
-                    entry.key().clone(),
-                    entry.value().last_accessed,
-                    entry.value().created_at,
-                )
-            })
-            .collect();
-        entries.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.2.cmp(&b.2)));
-        for (key, _, _) in entries.into_iter().take(count) {
-            self.storage.remove(&key);
+impl<V> Fetched<V> {
+    /// Returns true if the value came from the cache
+    pub fn is_hit(&self) -> bool {
+        matches!(self, Fetched::Hit(_))
+    }
+
+    /// Returns a reference to the wrapped value
+    pub fn value(&self) -> &V {
+        match self {
+            Fetched::Hit(value) | Fetched::Computed(value) => value,
         }
     }
 
-    /// Updates global cache statistics after cache state changes
-    fn update_cache_stats(&self) {
-        let name = format!("Cache@{:p}", self);
-        let stats = CacheStats {
-            name: name.clone(),
-            hits: self.hit_count(),
-            misses: self.miss_count(),
-            entries: self.len() as u64,
-            hit_rate: self.hit_rate(),
-        };
-        register_stats(name, stats);
+    /// Consumes the outcome, returning the wrapped value
+    pub fn into_value(self) -> V {
+        match self {
+            Fetched::Hit(value) | Fetched::Computed(value) => value,
+        }
     }
 }
 
-impl<K, V> Default for Cache<K, V>
+/// Generic cache supporting configurable eviction policies and TTL
+///
+/// Values are cloned out on every hit. For values that are large or not
+/// `Clone`, use `ArcCache`, which stores `Arc<V>` and hands out shared pointers.
+pub struct Cache<K, V, S = RandomState>
 where
     K: Hash + Eq + Clone,
     V: Clone,
+    S: BuildHasher + Clone,
 {
-    fn default() -> Self {
-        Self::new()
-    }
+    storage: Arc<DashMap<Arc<K>, CacheEntry<V>, S>>,
+    id: u64, // Unique per cache (shared by its clones); owns its global stats row
+    policy: Arc<RwLock<EvictionPolicy>>,
+    hits: Arc<std::sync::atomic::AtomicU64>,
+    misses: Arc<std::sync::atomic::AtomicU64>,
+    deduped: Arc<std::sync::atomic::AtomicU64>,
+    jitter: f64,
+    low_watermark: f64,
+    expiry: ExpiryStrategy,
+    compute_policy: ComputePolicy,
+    compute_locks: Arc<DashMap<K, Arc<Mutex<()>>>>,
+    refresh: Option<Arc<RefreshAhead<K, V, S>>>,
+    bytes: Arc<std::sync::atomic::AtomicU64>,
+    window: Arc<HitWindow>,
+    latency: Arc<LatencyRecorder>,
+    weigher: Option<Weigher<K, V>>,
+    stale_if_error: Option<Duration>,
+    sketch: Arc<Mutex<Option<FrequencySketch>>>,
+    name: Option<Arc<str>>,
+    on_evict: Option<EvictionListener<K, V>>,
+    pinned: Arc<DashMap<K, ()>>,
+    clock: Arc<dyn Clock>,
+    tuner: Arc<AtomicU64>, // Generation of the running autotuner; older ones stop when it moves
+    #[cfg(feature = "async")]
+    inflight: Arc<DashMap<K, Arc<tokio::sync::Mutex<()>>>>,
+    #[cfg(feature = "tracing")]
+    trace_keys: Option<KeyFormatter<K>>,
 }
 
-impl<K, V> Clone for Cache<K, V>
+/// A cache handle for background workers that doesn't keep the entries alive
+///
+/// Holds the storage weakly and everything else strongly, so a worker can
+/// rebuild a full handle each tick and stop once `upgrade` fails.
+pub(crate) struct WeakCache<K, V, S = RandomState>
 where
     K: Hash + Eq + Clone,
     V: Clone,
+    S: BuildHasher + Clone,
 {
-    fn clone(&self) -> Self {
-        Self {
-            storage: Arc::clone(&self.storage),
-            policy: self.policy.clone(),
-            hits: Arc::clone(&self.hits),
-            misses: Arc::clone(&self.misses),
-        }
+    storage: Weak<DashMap<Arc<K>, CacheEntry<V>, S>>,
+    settings: Cache<K, V, S>, // Every field but `storage`, which is an unused empty map
+}
+
+impl<K, V, S> WeakCache<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+{
+    /// Returns a full handle, or None once every strong handle has been dropped
+    pub(crate) fn upgrade(&self) -> Option<Cache<K, V, S>> {
+        self.storage.upgrade().map(|storage| Cache {
+            storage,
+            ..self.settings.clone()
+        })
     }
 }
 
-// --- GLOBAL CACHE STORAGE ---
+thread_local! {
+    /// Keys this thread is computing, as (storage address, key hash, key address), innermost last
+    static COMPUTING: RefCell<Vec<(usize, u64, usize)>> = const { RefCell::new(Vec::new()) };
+}
 
-/// Global thread-safe registry of caches by namespace and policy
-static GLOBAL_CACHE_STORAGE: OnceLock<Arc<DashMap<String, Cache<String, String>>>> =
-    OnceLock::new();
+#[cfg(feature = "async")]
+tokio::task_local! {
+    /// Keys the current task is computing in `get_async`, in the same form as `COMPUTING`
+    static ASYNC_COMPUTING: Vec<(usize, u64, usize)>;
+}
 
-/// Returns global cache storage singleton
-fn get_global_cache_storage() -> &'static Arc<DashMap<String, Cache<String, String>>> {
-    GLOBAL_CACHE_STORAGE.get_or_init(|| Arc::new(DashMap::new()))
+/// Returns whether `key` of the cache at `storage` is among the keys being computed
+///
+/// Hashes are only a prefilter: distinct keys that collide are compared by value.
+fn is_computing<K: Eq>(
+    computing: &[(usize, u64, usize)],
+    storage: usize,
+    hash: u64,
+    key: &K,
+) -> bool {
+    computing
+        .iter()
+        .any(|&(other_storage, other_hash, other_key)| {
+            // SAFETY: an entry is only listed while the guard or task-local scope
+            // that added it is alive, and both live inside a call borrowing its
+            // key. A matching storage address means the same live cache, so the
+            // key type is `K`.
+            other_storage == storage
+                && other_hash == hash
+                && unsafe { &*(other_key as *const K) } == key
+        })
 }
 
-/// Creates or retrieves a cache instance by namespace and eviction policy
-fn get_or_create_cache(namespace: &str, policy: EvictionPolicy) -> Cache<String, String> {
-    let caches = get_global_cache_storage();
-    // Compose key by combining namespace and policy description
-    let policy_key = match &policy {
-        EvictionPolicy::None => "none".to_string(),
-        EvictionPolicy::Lru(limit) => format!("lru({})", limit),
-        EvictionPolicy::Ttl { duration, ttl_type } => format!("ttl({:?},{:?})", duration, ttl_type),
-        EvictionPolicy::LruTtl {
-            limit,
-            duration,
-            ttl_type,
-        } => format!("lru_ttl({}, {:?},{:?})", limit, duration, ttl_type),
-    };
-    let cache_key = format!("{}::{}", namespace, policy_key);
-    caches
-        .entry(cache_key)
-        .or_insert_with(|| Cache::with_policy(policy))
-        .clone()
+/// Panics for a compute that asked its own cache for the key it is computing
+fn cyclic_computation() -> ! {
+    panic!("cyclic cache computation: compute asked its own cache for the key it is computing");
 }
 
-// --- Cache API functions ---
+/// Marks a key as being computed on this thread until dropped
+struct Computing((usize, u64, usize));
 
-pub fn cache_get<F, V>(namespace: &str, key: &str, compute: F) -> V
-where
-    F: FnOnce() -> V,
-    V: Clone + ToString + std::str::FromStr,
-    V::Err: std::fmt::Debug,
-{
-    let cache = get_or_create_cache(namespace, EvictionPolicy::None);
-    let cached_value = cache.get(&key.to_string(), || compute().to_string());
-    cached_value
-        .parse::<V>()
-        .expect("Failed to parse cached value")
+impl Computing {
+    /// Registers `key` of the cache at `storage`, panicking if this thread is already computing it
+    ///
+    /// A compute closure that asks its own cache for its own key would
+    /// otherwise recurse until the stack overflows, or deadlock on the key's
+    /// lock under `ComputePolicy::Serialized`. Distinct keys whose hashes
+    /// collide may nest freely.
+    fn enter<K: Eq>(storage: usize, hash: u64, key: &K) -> Self {
+        let id = (storage, hash, key as *const K as usize);
+        let cyclic = COMPUTING.with_borrow_mut(|computing| {
+            let cyclic = is_computing(computing, storage, hash, key);
+            if !cyclic {
+                computing.push(id);
+            }
+            cyclic
+        });
+        if cyclic {
+            cyclic_computation();
+        }
+        Computing(id)
+    }
 }
 
-pub fn cache_get_with_ttl<F, V>(
-    namespace: &str,
-    key: &str,
-    ttl: Duration,
-    ttl_type: TtlType,
-    compute: F,
-) -> V
-where
-    F: FnOnce() -> V,
-    V: Clone + ToString + std::str::FromStr,
-    V::Err: std::fmt::Debug,
-{
-    let cache = get_or_create_cache(
-        namespace,
-        EvictionPolicy::Ttl {
-            duration: ttl,
-            ttl_type,
-        },
-    );
-    let cached_value = cache.get(&key.to_string(), || compute().to_string());
-    cached_value
-        .parse::<V>()
-        .expect("Failed to parse cached value")
+impl Drop for Computing {
+    fn drop(&mut self) {
+        COMPUTING.with_borrow_mut(|computing| {
+            if let Some(pos) = computing.iter().rposition(|id| *id == self.0) {
+                computing.remove(pos);
+            }
+        });
+    }
 }
 
-pub fn cache_get_with_limit<F, V>(namespace: &str, key: &str, limit: usize, compute: F) -> V
+/// Removes the in-flight marker of a `get_async` computation, even if its future is dropped
+#[cfg(feature = "async")]
+struct Inflight<'a, K>
 where
-    F: FnOnce() -> V,
-    V: Clone + ToString + std::str::FromStr,
-    V::Err: std::fmt::Debug,
+    K: Hash + Eq,
 {
-    let cache = get_or_create_cache(namespace, EvictionPolicy::Lru(limit));
-    let cached_value = cache.get(&key.to_string(), || compute().to_string());
-    cached_value
-        .parse::<V>()
-        .expect("Failed to parse cached value")
+    pending: &'a DashMap<K, Arc<tokio::sync::Mutex<()>>>,
+    key: &'a K,
+    lock: &'a Arc<tokio::sync::Mutex<()>>,
 }
 
-pub fn cache_get_with_ttl_and_limit<F, V>(
-    namespace: &str,
-    key: &str,
-    ttl: Duration,
-    limit: usize,
-    ttl_type: TtlType,
-    compute: F,
-) -> V
+#[cfg(feature = "async")]
+impl<K> Drop for Inflight<'_, K>
 where
-    F: FnOnce() -> V,
-    V: Clone + ToString + std::str::FromStr,
-    V::Err: std::fmt::Debug,
+    K: Hash + Eq,
 {
-    let cache = get_or_create_cache(
-        namespace,
-        EvictionPolicy::LruTtl {
-            limit,
-            duration: ttl,
-            ttl_type,
-        },
-    );
-    let cached_value = cache.get(&key.to_string(), || compute().to_string());
-    cached_value
-        .parse::<V>()
-        .expect("Failed to parse cached value")
+    fn drop(&mut self) {
+        // A cancelled computation may already have been replaced by a newer caller's
+        self.pending
+            .remove_if(self.key, |_, pending| Arc::ptr_eq(pending, self.lock));
+    }
 }
 
-/// Invalidate entry by key in all caches with the given namespace
-pub fn cache_invalidate(namespace: &str, key: &str) -> bool {
-    let caches = get_global_cache_storage();
-    let mut invalidated = false;
-    for cache in caches.iter() {
-        if cache.key().starts_with(namespace) && cache.value().invalidate(&key.to_string()) {
-            invalidated = true;
+/// Orders `recency_snapshot` items from least to most recently used, ties broken by creation
+fn by_recency<K>(a: &(K, Instant, Instant), b: &(K, Instant, Instant)) -> std::cmp::Ordering {
+    a.1.cmp(&b.1).then_with(|| a.2.cmp(&b.2))
+}
+
+/// Takes a key out of its storage `Arc`, cloning it only if the `Arc` is still shared
+fn unwrap_key<K: Clone>(key: Arc<K>) -> K {
+    Arc::try_unwrap(key).unwrap_or_else(|key| K::clone(&key))
+}
+
+/// Window over which stats report `recent_hit_rate`
+pub(crate) const RECENT_HIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Entries sampled to pick the victim a new `TinyLfu` key must out-rank
+const ADMISSION_SAMPLE: usize = 8;
+
+/// Source of `Cache::id`
+static NEXT_CACHE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Estimates the memory footprint in bytes of a single entry
+pub(crate) type Weigher<K, V> = Arc<dyn Fn(&K, &V) -> u64 + Send + Sync>;
+
+/// Callback told about entries removed by LRU eviction or expiry
+pub(crate) type EvictionListener<K, V> = Arc<dyn Fn(&K, &V) + Send + Sync>;
+
+/// Renders keys for `tracing` events; without one they are redacted
+#[cfg(feature = "tracing")]
+pub(crate) type KeyFormatter<K> = Arc<dyn Fn(&K) -> String + Send + Sync>;
+
+/// What happened to a key, as reported to `tracing`
+#[derive(Clone, Copy)]
+enum Traced {
+    Hit,
+    Miss,
+    Evicted,
+    Expired,
+}
+
+/// Starts a background refresh of a key, given a handle to the cache that hit it
+type RefreshSpawner<K, V, S> = Box<dyn Fn(K, WeakCache<K, V, S>) + Send + Sync>;
+
+/// Refresh-ahead configuration: recomputes entries in the background as they near expiry
+struct RefreshAhead<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+{
+    threshold: f64,
+    pending: Arc<DashMap<K, ()>>,
+    spawn: RefreshSpawner<K, V, S>,
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Creates a new cache without eviction policy
+    pub fn new() -> Self {
+        Self::with_policy(EvictionPolicy::None)
+    }
+
+    /// Starts a `CacheBuilder` for combining several options
+    pub fn builder() -> CacheBuilder<K, V> {
+        CacheBuilder::new()
+    }
+
+    /// Creates a cache that never stores anything, for measuring what caching saves
+    ///
+    /// Shorthand for `with_policy(EvictionPolicy::Disabled)`.
+    pub fn disabled() -> Self {
+        Self::with_policy(EvictionPolicy::Disabled)
+    }
+
+    /// Creates a new cache with specified eviction policy
+    pub fn with_policy(policy: EvictionPolicy) -> Self {
+        Self::with_hasher(policy, RandomState::new())
+    }
+
+    /// Creates a new cache whose entry TTLs are randomized by up to `jitter` either way
+    ///
+    /// `jitter` is a fraction clamped to [0.0, 1.0]; with 0.1 a 60s TTL lands each
+    /// entry somewhere in 54-66s, spreading out the expiry of entries written together.
+    pub fn with_policy_jitter(policy: EvictionPolicy, jitter: f64) -> Self {
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            ..Self::with_policy(policy)
+        }
+    }
+
+    /// Creates a new cache that, once over its LRU limit, evicts down to `limit * low_watermark`
+    ///
+    /// `low_watermark` is a fraction clamped to [0.0, 1.0]; with 0.9 a cache limited
+    /// to 100 entries evicts 11 entries when the 101st arrives, then absorbs the
+    /// next ten inserts without evicting. The default of 1.0 evicts to exactly
+    /// the limit.
+    pub fn with_watermark(policy: EvictionPolicy, low_watermark: f64) -> Self {
+        Self {
+            low_watermark: low_watermark.clamp(0.0, 1.0),
+            ..Self::with_policy(policy)
+        }
+    }
+
+    /// Creates a new cache that removes expired entries according to `expiry`
+    ///
+    /// `SweepOnWrite` (the default) scans the whole map on every write, which
+    /// gets slow for large caches. `OnAccessOnly` skips the scan; expired entries
+    /// still count towards `len` and LRU limits until read or purged.
+    /// `Reaper(interval)` spawns a thread that calls `purge_expired` every
+    /// `interval`; it stops once every other handle to the cache is dropped.
+    ///
+    /// For large memory-bounded caches prefer `Reaper`: inserts then only check
+    /// the LRU limit and evict the overflow, so their cost stays flat as the
+    /// cache fills (see `benches/insert.rs`).
+    pub fn with_expiry(policy: EvictionPolicy, expiry: ExpiryStrategy) -> Self
+    where
+        K: Send + Sync + 'static,
+        V: Send + Sync + 'static,
+    {
+        let cache = Self {
+            expiry,
+            ..Self::with_policy(policy)
+        };
+        cache.spawn_reaper();
+        cache
+    }
+
+    /// Creates a new cache that serves expired values when recomputing them fails
+    ///
+    /// Expired entries are kept for up to `max_stale` past their expiry instead
+    /// of being removed on access. If `get_or_try` then fails to recompute one,
+    /// the stale value is returned in place of the error (`get_or_try_tracked`
+    /// reports it as `CacheOutcome::Stale`) and stays cached until a successful
+    /// recompute replaces it. Retained entries count towards `len` and LRU limits.
+    pub fn with_stale_if_error(policy: EvictionPolicy, max_stale: Duration) -> Self {
+        Self {
+            stale_if_error: Some(max_stale),
+            ..Self::with_policy(policy)
+        }
+    }
+
+    /// Creates a new cache whose storage is split into `shard_amount` locked shards
+    ///
+    /// More shards mean less lock contention between threads touching different
+    /// keys, at the cost of some memory per shard. The default is four times the
+    /// core count, rounded up to a power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_amount` is not a power of two greater than 1.
+    pub fn with_shards(policy: EvictionPolicy, shard_amount: usize) -> Self {
+        Self {
+            storage: Arc::new(DashMap::with_shard_amount(shard_amount)),
+            ..Self::with_policy(policy)
+        }
+    }
+
+    /// Creates a new cache with room for `capacity` entries before its storage grows
+    ///
+    /// Avoids repeated resizing while a cache of known size is filled, e.g.
+    /// by `warm`. Capacity is only preallocated; it does not limit the cache.
+    pub fn with_capacity(policy: EvictionPolicy, capacity: usize) -> Self {
+        Self {
+            storage: Arc::new(DashMap::with_capacity(capacity)),
+            ..Self::with_policy(policy)
+        }
+    }
+
+    /// Creates a new cache that computes concurrent misses on a key according to `compute`
+    ///
+    /// `Concurrent` (the default) never blocks but lets simultaneous misses on
+    /// one key each run `compute`. `Serialized` makes them queue on a per-key
+    /// lock so `get` and `get_or_try` compute each missing key once, at the cost
+    /// of the waiters' latency. Different keys never block each other.
+    pub fn with_compute_policy(policy: EvictionPolicy, compute: ComputePolicy) -> Self {
+        Self {
+            compute_policy: compute,
+            ..Self::with_policy(policy)
+        }
+    }
+
+    /// Creates a new cache that sizes entries with `weigher` for `approx_memory_bytes`
+    ///
+    /// Without a weigher each entry counts as `size_of::<K>() + size_of::<V>()`,
+    /// which ignores heap data such as string contents.
+    pub fn with_weigher<W>(policy: EvictionPolicy, weigher: W) -> Self
+    where
+        W: Fn(&K, &V) -> u64 + Send + Sync + 'static,
+    {
+        Self {
+            weigher: Some(Arc::new(weigher)),
+            ..Self::with_policy(policy)
+        }
+    }
+
+    /// Creates a new cache that refreshes entries in the background before they expire
+    ///
+    /// When a hit finds an entry with at most `threshold` (a fraction of its TTL)
+    /// remaining, the current value is returned and `refresh` is run on a
+    /// background thread to replace it. Only one refresh per key runs at a time.
+    /// Entries without a TTL are never refreshed.
+    pub fn with_refresh_ahead<R>(policy: EvictionPolicy, threshold: f64, refresh: R) -> Self
+    where
+        R: Fn(&K) -> V + Send + Sync + 'static,
+        K: Send + Sync + 'static,
+        V: Send + Sync + 'static,
+    {
+        let mut cache = Self::with_policy(policy);
+        let pending: Arc<DashMap<K, ()>> = Arc::new(DashMap::new());
+        let refresh = Arc::new(refresh);
+        let spawn_pending = Arc::clone(&pending);
+        let spawn = move |key: K, refreshed: WeakCache<K, V>| {
+            let pending = Arc::clone(&spawn_pending);
+            let refresh = Arc::clone(&refresh);
+            std::thread::spawn(move || {
+                let value = refresh(&key);
+                if let Some(cache) = refreshed.upgrade() {
+                    cache.store_refreshed(&key, value);
+                }
+                pending.remove(&key);
+            });
+        };
+        cache.refresh = Some(Arc::new(RefreshAhead {
+            threshold: threshold.clamp(0.0, 1.0),
+            pending,
+            spawn: Box::new(spawn),
+        }));
+        cache
+    }
+}
+
+impl<K, V, S> Cache<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+{
+    /// Creates a new cache with specified eviction policy and key hasher
+    ///
+    /// Useful for swapping the default SipHash for a faster hasher on trusted keys.
+    pub fn with_hasher(policy: EvictionPolicy, hasher: S) -> Self {
+        Self {
+            storage: Arc::new(DashMap::with_hasher(hasher)),
+            id: NEXT_CACHE_ID.fetch_add(1, Ordering::Relaxed),
+            policy: Arc::new(RwLock::new(policy)),
+            hits: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            misses: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            deduped: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            jitter: 0.0,
+            low_watermark: 1.0,
+            expiry: ExpiryStrategy::SweepOnWrite,
+            compute_policy: ComputePolicy::Concurrent,
+            compute_locks: Arc::new(DashMap::new()),
+            refresh: None,
+            bytes: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            window: Arc::new(HitWindow::new()),
+            latency: Arc::new(LatencyRecorder::new()),
+            weigher: None,
+            stale_if_error: None,
+            sketch: Arc::new(Mutex::new(None)),
+            name: None,
+            on_evict: None,
+            pinned: Arc::new(DashMap::new()),
+            clock: Arc::new(SystemClock),
+            tuner: Arc::new(AtomicU64::new(0)),
+            #[cfg(feature = "async")]
+            inflight: Arc::new(DashMap::new()),
+            #[cfg(feature = "tracing")]
+            trace_keys: None,
+        }
+    }
+
+    /// Assembles a cache from a builder's settings
+    pub(crate) fn from_builder(builder: CacheBuilder<K, V, S>) -> Self
+    where
+        K: Send + Sync + 'static,
+        V: Send + Sync + 'static,
+        S: Send + Sync + 'static,
+    {
+        let hasher = builder.hasher;
+        let capacity = builder.initial_capacity;
+        let storage = match builder.shards {
+            Some(shards) => {
+                DashMap::with_capacity_and_hasher_and_shard_amount(capacity, hasher.clone(), shards)
+            }
+            None => DashMap::with_capacity_and_hasher(capacity, hasher.clone()),
+        };
+        let cache = Self {
+            storage: Arc::new(storage),
+            jitter: builder.jitter.clamp(0.0, 1.0),
+            low_watermark: builder.low_watermark.clamp(0.0, 1.0),
+            expiry: builder.expiry,
+            compute_policy: builder.compute_policy,
+            weigher: builder.weigher,
+            stale_if_error: builder.stale_if_error,
+            name: builder.name.map(Arc::from),
+            on_evict: builder.on_evict,
+            clock: builder.clock,
+            #[cfg(feature = "tracing")]
+            trace_keys: builder.trace_keys,
+            ..Self::with_hasher(builder.policy, hasher)
+        };
+        cache.spawn_reaper();
+        cache
+    }
+
+    /// Starts the background sweep thread if the expiry strategy is `Reaper`
+    fn spawn_reaper(&self)
+    where
+        K: Send + Sync + 'static,
+        V: Send + Sync + 'static,
+        S: Send + Sync + 'static,
+    {
+        if let ExpiryStrategy::Reaper(interval) = self.expiry {
+            let reaper = self.downgrade();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(interval);
+                match reaper.upgrade() {
+                    Some(cache) => {
+                        cache.purge_expired();
+                    }
+                    None => break,
+                }
+            });
+        }
+    }
+
+    /// Spawns a task on the current tokio runtime that calls `purge_expired` every `interval`
+    ///
+    /// The async counterpart of `ExpiryStrategy::Reaper` for apps that would
+    /// rather not dedicate a thread to it; use it with `OnAccessOnly` so
+    /// writes don't scan as well. The task ends when `ReaperStop::stop` is
+    /// called or once every handle to the cache is dropped.
+    ///
+    /// # Panics
+    /// Panics if called outside a tokio runtime.
+    #[cfg(feature = "async")]
+    pub fn spawn_async_reaper(
+        &self,
+        interval: Duration,
+    ) -> (tokio::task::JoinHandle<()>, ReaperStop)
+    where
+        K: Send + Sync + 'static,
+        V: Send + Sync + 'static,
+        S: Send + Sync + 'static,
+    {
+        let stop = ReaperStop {
+            notify: Arc::new(tokio::sync::Notify::new()),
+        };
+        let stopped = Arc::clone(&stop.notify);
+        let reaper = self.downgrade();
+        let task = tokio::spawn(async move {
+            // Waking early means `stop` was called
+            while tokio::time::timeout(interval, stopped.notified())
+                .await
+                .is_err()
+            {
+                match reaper.upgrade() {
+                    Some(cache) => {
+                        cache.purge_expired();
+                    }
+                    None => break,
+                }
+            }
+        });
+        (task, stop)
+    }
+
+    /// Retrieves cached value or computes and caches it
+    ///
+    /// Updates statistics after access and maintains eviction as needed.
+    /// `compute` runs only on a miss and at most once, so any `FnOnce` works,
+    /// including closures that mutably borrow local state.
+    pub fn get<F>(&self, key: &K, compute: F) -> V
+    where
+        F: FnOnce() -> V,
+    {
+        self.get_tracked(key, compute).0
+    }
+
+    /// Like `get`, but also reports whether the value was cached or computed
+    ///
+    /// `Refreshed` is a hit that also started a refresh-ahead recomputation.
+    pub fn get_tracked<F>(&self, key: &K, compute: F) -> (V, CacheOutcome)
+    where
+        F: FnOnce() -> V,
+    {
+        match self.try_get_tracked(key, || Ok::<_, std::convert::Infallible>(compute())) {
+            Ok(fetched) => fetched,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Retrieves cached value or runs a fallible `compute` and caches its output
+    ///
+    /// On `Err` nothing is cached and the error is returned; the miss is still counted.
+    pub fn get_or_try<F, E>(&self, key: &K, compute: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        self.try_get_tracked(key, compute).map(|(value, _)| value)
+    }
+
+    /// `get_or_try` that also reports how the value was obtained
+    ///
+    /// `CacheOutcome::Stale` means `compute` failed and an expired value was
+    /// served; see `with_stale_if_error`.
+    pub fn get_or_try_tracked<F, E>(&self, key: &K, compute: F) -> Result<(V, CacheOutcome), E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        self.try_get_tracked(key, compute)
+    }
+
+    /// Shared body of `get_tracked` and `get_or_try`
+    fn try_get_tracked<F, E>(&self, key: &K, compute: F) -> Result<(V, CacheOutcome), E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        // A single `get_mut` checks, touches and clones the entry, so an eviction
+        // cannot slip in between finding the entry and reading it
+        if let Some(found) = self.lookup_outcome(key) {
+            self.update_cache_stats();
+            return Ok(found);
+        }
+        match self.compute_missing(key, || compute().map(|value| self.new_entry(value))) {
+            Err(err) => match self.servable_stale_value(key) {
+                Some(value) => Ok((value, CacheOutcome::Stale)),
+                None => Err(err),
+            },
+            found => found,
+        }
+    }
+
+    /// Retrieves cached value or computes it along with its own TTL
+    ///
+    /// `compute` returns the value and, optionally, a fixed TTL for that entry
+    /// (e.g. from an HTTP `Cache-Control: max-age`). `None` falls back to the
+    /// policy's TTL settings.
+    pub fn get_with_dynamic_ttl<F>(&self, key: &K, compute: F) -> V
+    where
+        F: FnOnce() -> (V, Option<Duration>),
+    {
+        if let Some(value) = self.get_if_cached(key) {
+            return value;
+        }
+        let computed = self.compute_missing(key, || {
+            let (value, ttl) = compute();
+            Ok::<_, std::convert::Infallible>(match ttl {
+                Some(ttl) => {
+                    CacheEntry::new_at(value, Some(ttl), Some(TtlType::Fixed), self.clock.now())
+                }
+                None => self.new_entry(value),
+            })
+        });
+        match computed {
+            Ok((value, _)) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Retrieves cached value or computes it, storing it only if `compute` allows
+    ///
+    /// `compute` returns the value and whether it may be cached. A declined
+    /// value (say, a placeholder) is returned without being stored; the miss is
+    /// still counted, so the next call computes again.
+    pub fn get_cacheable<F>(&self, key: &K, compute: F) -> V
+    where
+        F: FnOnce() -> (V, bool),
+    {
+        if let Some(value) = self.get_if_cached(key) {
+            return value;
+        }
+        let computed = self.compute_missing(key, || match compute() {
+            (value, true) => Ok(self.new_entry(value)),
+            (value, false) => Err(value),
+        });
+        match computed {
+            Ok((value, _)) | Err(value) => value,
+        }
+    }
+
+    /// Retrieves cached value if it was stored for `version`, otherwise computes it
+    ///
+    /// The entry remembers the version it was computed for; a call with a
+    /// different version drops it and recomputes, as does TTL expiry. Entries
+    /// stored through other methods carry no version and are always recomputed.
+    pub fn get_versioned<F>(&self, key: &K, version: u64, compute: F) -> V
+    where
+        F: FnOnce() -> V,
+    {
+        match self.read_entry(key, |entry| entry.version == Some(version)) {
+            Some(true) => {
+                if let Some(value) = self.get_if_cached(key) {
+                    return value;
+                }
+            }
+            // Dropped first so a `Serialized` compute doesn't find and serve it
+            Some(false) => {
+                self.remove_key(key);
+            }
+            None => {}
+        }
+        let computed = self.compute_missing(key, || {
+            let mut entry = self.new_entry(compute());
+            entry.version = Some(version);
+            Ok::<_, std::convert::Infallible>(entry)
+        });
+        match computed {
+            Ok((value, _)) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Retrieves cached value or awaits `compute` and caches its output
+    ///
+    /// Concurrent callers missing on the same key share a single computation:
+    /// the first caller runs `compute` while the others wait and then read the
+    /// freshly cached value.
+    #[cfg(feature = "async")]
+    pub async fn get_async<F, Fut>(&self, key: &K, compute: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        if let Some(value) = self.get_if_cached(key) {
+            return value;
+        }
+        // The task's own compute would wait on the key's lock forever
+        let storage = Arc::as_ptr(&self.storage) as usize;
+        let hash = self.storage.hasher().hash_one(key);
+        let mut computing = ASYNC_COMPUTING.try_with(Vec::clone).unwrap_or_default();
+        if is_computing(&computing, storage, hash, key) {
+            cyclic_computation();
+        }
+        computing.push((storage, hash, key as *const K as usize));
+        let lock = self
+            .inflight
+            .entry(key.clone())
+            .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+            .clone();
+        let _guard = lock.lock().await;
+        let _inflight = Inflight {
+            pending: &self.inflight,
+            key,
+            lock: &lock,
+        };
+        // Another caller may have finished the computation while we waited
+        if let Some(value) = self.get_if_cached(key) {
+            self.deduped.fetch_add(1, Ordering::Relaxed);
+            return value;
+        }
+        self.record_misses(1);
+        self.trace(key, Traced::Miss);
+        let started = Instant::now();
+        let value = ASYNC_COMPUTING.scope(computing, compute()).await;
+        self.latency.record(started.elapsed());
+        self.insert(key.clone(), value.clone());
+        value
+    }
+
+    /// Retrieves cached value or computes it, giving up on `compute` after `timeout`
+    ///
+    /// `compute` runs on its own thread. If it has not finished in time the
+    /// caller gets the key's expired entry, if one is still stored, or
+    /// `CacheError::Timeout`. The abandoned computation keeps running and caches
+    /// its value if it eventually finishes; a panic in it propagates to the caller
+    /// only if it happens before the timeout. Under `ComputePolicy::Serialized`
+    /// the thread takes the key's lock, so concurrent callers share one computation.
+    pub fn get_with_timeout<F>(
+        &self,
+        key: &K,
+        timeout: Duration,
+        compute: F,
+    ) -> Result<V, CacheError>
+    where
+        K: Send + Sync + 'static,
+        V: Send + Sync + 'static,
+        S: Send + Sync + 'static,
+        F: FnOnce() -> V + Send + 'static,
+    {
+        let stale = self.stale_value(key);
+        if let Some(value) = self.get_if_cached(key) {
+            return Ok(value);
+        }
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let cache = self.clone();
+        let owned_key = key.clone();
+        std::thread::spawn(move || {
+            // Caught so the caller can rethrow the original payload
+            let computed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                cache.compute_missing(&owned_key, || {
+                    Ok::<_, std::convert::Infallible>(cache.new_entry(compute()))
+                })
+            }));
+            let _ = sender.send(computed.map(|computed| match computed {
+                Ok((value, _)) => value,
+                Err(never) => match never {},
+            }));
+        });
+        let result = match receiver.recv_timeout(timeout) {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(panic)) => std::panic::resume_unwind(panic),
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                stale.ok_or(CacheError::Timeout(timeout))
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                unreachable!("the compute thread always reports back")
+            }
+        };
+        self.update_cache_stats();
+        result
+    }
+
+    /// Async variant of `get_with_timeout`; a timed-out `compute` future is dropped
+    ///
+    /// Concurrent callers share one computation as in `get_async`.
+    #[cfg(feature = "async")]
+    pub async fn get_with_timeout_async<F, Fut>(
+        &self,
+        key: &K,
+        timeout: Duration,
+        compute: F,
+    ) -> Result<V, CacheError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let stale = self.stale_value(key);
+        match tokio::time::timeout(timeout, self.get_async(key, compute)).await {
+            Ok(value) => Ok(value),
+            Err(_) => stale.ok_or(CacheError::Timeout(timeout)),
+        }
+    }
+
+    /// Attempts to retrieve cached value without computing
+    ///
+    /// Counts as an access: a found entry is touched (refreshing its LRU
+    /// position and sliding TTL) and recorded as a hit. Use `peek` for a
+    /// side-effect-free check.
+    pub fn get_if_cached(&self, key: &K) -> Option<V> {
+        let value = self.lookup(key);
+        self.update_cache_stats();
+        value
+    }
+
+    /// Returns a live entry's value without touching it or counting a hit
+    ///
+    /// LRU order, sliding TTLs, access counts and hit/miss statistics are left
+    /// unchanged, so it is safe to call from metrics and debugging code.
+    pub fn peek(&self, key: &K) -> Option<V> {
+        self.read_entry(key, |entry| entry.value.clone())
+    }
+
+    /// Inserts a value directly into the cache, returning the live value it replaced
+    ///
+    /// An expired entry that had not been removed yet is dropped and not returned.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.insert_entry(key, self.new_entry(value))
+    }
+
+    /// Inserts a value with its own TTL, overriding the policy's TTL for this entry
+    ///
+    /// Returns the live value it replaced, as `insert` does.
+    pub fn insert_with_ttl(&self, key: K, value: V, ttl: Duration, ttl_type: TtlType) -> Option<V> {
+        let entry = CacheEntry::new_at(value, Some(ttl), Some(ttl_type), self.clock.now());
+        self.insert_entry(key, entry)
+    }
+
+    /// Inserts a value tagged with `metadata`, readable later through `get_meta`
+    ///
+    /// Returns the live value it replaced, as `insert` does.
+    pub fn insert_with_meta(
+        &self,
+        key: K,
+        value: V,
+        metadata: HashMap<String, String>,
+    ) -> Option<V> {
+        let mut entry = self.new_entry(value);
+        entry.metadata = Some(Box::new(metadata));
+        self.insert_entry(key, entry)
+    }
+
+    /// Returns a live entry's metadata without touching it
+    ///
+    /// `None` if the key is absent or expired, or was inserted without metadata.
+    pub fn get_meta(&self, key: &K) -> Option<HashMap<String, String>> {
+        self.read_entry(key, |entry| entry.metadata.as_deref().cloned())
+            .flatten()
+    }
+
+    /// Retrieves several keys at once, computing all missing ones in a single call
+    ///
+    /// `compute_missing` receives the keys that were absent or expired and returns
+    /// the values it could produce. Results follow the order of `keys`; a key the
+    /// closure did not return a value for yields `None`. Returned pairs for keys
+    /// it was not asked for are dropped rather than cached.
+    ///
+    /// `compute_missing` is `FnOnce` and called at most once (not at all when
+    /// every key hits), so it may move out of or mutably borrow captured data.
+    /// It runs without the per-key locks even under `ComputePolicy::Serialized`.
+    pub fn get_many<F>(&self, keys: &[K], compute_missing: F) -> Vec<Option<Fetched<V>>>
+    where
+        F: FnOnce(&[K]) -> Vec<(K, V)>,
+    {
+        let mut results: Vec<Option<Fetched<V>>> = keys
+            .iter()
+            .map(|key| self.lookup(key).map(Fetched::Hit))
+            .collect();
+        let mut requested: HashSet<&K> = HashSet::new();
+        let missing: Vec<K> = keys
+            .iter()
+            .zip(&results)
+            .filter(|(key, result)| result.is_none() && requested.insert(*key))
+            .map(|(key, _)| key.clone())
+            .collect();
+        if !missing.is_empty() {
+            self.record_misses(missing.len() as u64);
+            for key in &missing {
+                self.trace(key, Traced::Miss);
+            }
+            let started = Instant::now();
+            let computed: HashMap<K, V> = compute_missing(&missing)
+                .into_iter()
+                .filter(|(key, _)| requested.contains(key))
+                .collect();
+            self.latency.record(started.elapsed());
+            for (key, value) in &computed {
+                self.store_computed(key.clone(), self.new_entry(value.clone()));
+            }
+            self.maybe_evict();
+            for (key, result) in keys.iter().zip(results.iter_mut()) {
+                if result.is_none() {
+                    *result = computed.get(key).cloned().map(Fetched::Computed);
+                }
+            }
+        }
+        self.update_cache_stats();
+        results
+    }
+
+    /// Inserts several values, running eviction once after all of them are stored
+    pub fn insert_many<I>(&self, entries: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in entries {
+            self.store(key, self.new_entry(value));
+        }
+        self.maybe_evict();
+        self.update_cache_stats();
+    }
+
+    /// Returns the keys of all live entries, in no particular order
+    pub fn keys(&self) -> Vec<K> {
+        self.storage
+            .iter()
+            .filter(|entry| !self.expired(entry.key(), entry.value()))
+            .map(|entry| K::clone(entry.key()))
+            .collect()
+    }
+
+    /// Returns up to `n` live keys with the highest access counts, busiest first
+    pub fn top_keys(&self, n: usize) -> Vec<(K, u64)> {
+        if n == 0 {
+            return Vec::new();
+        }
+        let mut entries: Vec<(Arc<K>, u64)> = self
+            .storage
+            .iter()
+            .filter(|entry| !self.expired(entry.key(), entry.value()))
+            .map(|entry| (Arc::clone(entry.key()), entry.value().access_count))
+            .collect();
+        let busiest_first = |a: &(Arc<K>, u64), b: &(Arc<K>, u64)| b.1.cmp(&a.1);
+        if n < entries.len() {
+            entries.select_nth_unstable_by(n - 1, busiest_first);
+            entries.truncate(n);
+        }
+        entries.sort_unstable_by(busiest_first);
+        entries
+            .into_iter()
+            .map(|(key, count)| (K::clone(&key), count))
+            .collect()
+    }
+
+    /// Counts live entries by age, for choosing a TTL from how old entries get
+    ///
+    /// `buckets` are ascending upper bounds: slot `i` counts entries younger
+    /// than `buckets[i]` but not younger than `buckets[i - 1]`, and one extra
+    /// slot at the end counts the rest, so the result has `buckets.len() + 1`
+    /// slots. Ages are measured from creation at the time of the call.
+    pub fn age_histogram(&self, buckets: &[Duration]) -> Vec<usize> {
+        let now = self.clock.now();
+        let mut counts = vec![0; buckets.len() + 1];
+        for entry in self.storage.iter() {
+            if self.expired(entry.key(), entry.value()) {
+                continue;
+            }
+            let age = now.saturating_duration_since(entry.value().created_at);
+            counts[buckets.partition_point(|bound| *bound <= age)] += 1;
+        }
+        counts
+    }
+
+    /// Returns true if a live entry exists for the key, without touching it
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.read_entry(key, |_| ()).is_some()
+    }
+
+    /// Returns how long ago the entry was created, without touching it
+    pub fn entry_age(&self, key: &K) -> Option<Duration> {
+        let now = self.clock.now();
+        self.read_entry(key, |entry| now.saturating_duration_since(entry.created_at))
+    }
+
+    /// Returns the time left before the entry expires, or `None` if absent or without TTL
+    pub fn ttl_remaining(&self, key: &K) -> Option<Duration> {
+        self.read_entry(key, |entry| entry.ttl_remaining_at(self.clock.now()))
+            .flatten()
+    }
+
+    /// Returns the entry's TTL and TTL type, or `None` if absent, expired or without TTL
+    pub fn entry_ttl(&self, key: &K) -> Option<(Duration, TtlType)> {
+        self.read_entry(key, |entry| Some((entry.ttl?, entry.ttl_type.clone()?)))
+            .flatten()
+    }
+
+    /// Replaces the TTL of a live entry in place, returning false if absent or expired
+    ///
+    /// Expiry is measured as usual from creation (`Fixed`) or last access
+    /// (`Sliding`), so a longer `ttl` extends the entry without recomputing it.
+    /// Neither the value nor its LRU position changes.
+    pub fn set_entry_ttl(&self, key: &K, ttl: Duration, ttl_type: TtlType) -> bool {
+        match self.storage.get_mut(key) {
+            Some(mut entry) if !self.expired(key, &entry) => {
+                entry.ttl = Some(ttl);
+                entry.ttl_type = Some(ttl_type);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Marks an entry expired without removing it, returning false if the key is absent
+    ///
+    /// The entry's TTL is set to zero, so the next `get` recomputes it while
+    /// its timestamps, access count and metadata stay as they were until then.
+    /// That only holds under `ExpiryStrategy::OnAccessOnly`: like any expired
+    /// entry it is removed by the next write under the default `SweepOnWrite`,
+    /// and by the next sweep under `Reaper`. A pinned key is not affected
+    /// until it is unpinned.
+    pub fn expire_now(&self, key: &K) -> bool {
+        match self.storage.get_mut(key) {
+            Some(mut entry) => {
+                entry.ttl = Some(Duration::ZERO);
+                entry.ttl_type = Some(TtlType::Fixed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Mutates a live entry's value in place, returning false if absent or expired
+    ///
+    /// The entry is touched like a hit, without counting one, and an expired
+    /// entry is removed as on lookup. `f` runs while the entry's shard is
+    /// locked, so it must not call back into this cache.
+    pub fn update_in_place<F>(&self, key: &K, f: F) -> bool
+    where
+        F: FnOnce(&mut V),
+    {
+        let Some(mut entry) = self.storage.get_mut(key) else {
+            return false;
+        };
+        if self.expired(key, &entry) {
+            let keep = self.is_servable_stale(&entry);
+            drop(entry);
+            if !keep {
+                self.evict_key(key, Traced::Expired);
+                self.update_cache_stats();
+            }
+            return false;
+        }
+        let before = self.weigh(key, &entry.value);
+        f(&mut entry.value);
+        entry.touch_at(self.clock.now());
+        let after = self.weigh(key, &entry.value);
+        drop(entry);
+        self.bytes.fetch_add(after, Ordering::Relaxed);
+        self.release(before);
+        true
+    }
+
+    /// Returns how long ago the entry was last accessed, without touching it
+    pub fn last_accessed_age(&self, key: &K) -> Option<Duration> {
+        let now = self.clock.now();
+        self.read_entry(key, |entry| {
+            now.saturating_duration_since(entry.last_accessed)
+        })
+    }
+
+    /// Protects `key` from LRU eviction and TTL expiry until `unpin` is called
+    ///
+    /// The pin belongs to the key, not the entry: it may be set before the key
+    /// is inserted and survives re-inserts and invalidation. Pinned entries
+    /// still count towards `len` and LRU limits, so pinning as many keys as the
+    /// limit leaves no room for anything else.
+    pub fn pin(&self, key: &K) {
+        self.pinned.insert(key.clone(), ());
+    }
+
+    /// Removes a pin, returning false if the key was not pinned
+    ///
+    /// An entry whose TTL ran out while pinned is expired from then on.
+    pub fn unpin(&self, key: &K) -> bool {
+        self.pinned.remove(key).is_some()
+    }
+
+    /// Checks whether `key` is pinned
+    pub fn is_pinned(&self, key: &K) -> bool {
+        self.pinned.contains_key(key)
+    }
+
+    /// Preloads entries in bulk, applying the policy's TTL to each
+    ///
+    /// Eviction runs once after everything is stored rather than after every insert.
+    pub fn warm<I>(&self, entries: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        self.insert_many(entries);
+    }
+
+    /// Removes an entry by key, returns true if found and removed
+    pub fn invalidate(&self, key: &K) -> bool {
+        let removed = self.remove_key(key).is_some();
+        if removed {
+            self.update_cache_stats();
+        }
+        removed
+    }
+
+    /// Removes an entry by key, returning its value if it was live
+    pub fn invalidate_take(&self, key: &K) -> Option<V> {
+        let (_, entry) = self.remove_key(key)?;
+        self.update_cache_stats();
+        (!self.expired(key, &entry)).then_some(entry.value)
+    }
+
+    /// Removes all expired entries now, returning how many were removed
+    ///
+    /// Useful with `ExpiryStrategy::OnAccessOnly` to reclaim memory on your own schedule.
+    pub fn purge_expired(&self) -> usize {
+        let removed = self.sweep_expired();
+        if removed > 0 {
+            self.update_cache_stats();
+        }
+        removed
+    }
+
+    /// Returns the strategy used to remove expired entries
+    pub fn expiry_strategy(&self) -> ExpiryStrategy {
+        self.expiry
+    }
+
+    /// Removes every entry whose key starts with `prefix`, returning how many were removed
+    pub fn invalidate_prefix(&self, prefix: &str) -> usize
+    where
+        K: AsRef<str>,
+    {
+        let keys_to_remove: Vec<_> = self
+            .storage
+            .iter()
+            .filter(|entry| K::as_ref(entry.key()).starts_with(prefix))
+            .map(|entry| Arc::clone(entry.key()))
+            .collect();
+        let removed = keys_to_remove
+            .iter()
+            .filter(|key| self.remove_key(key).is_some())
+            .count();
+        if removed > 0 {
+            self.update_cache_stats();
+        }
+        removed
+    }
+
+    /// Keeps only the entries for which `f` returns true, removing the rest
+    ///
+    /// `f` is called once per stored entry in no particular order and may
+    /// mutate captured state.
+    pub fn retain<F>(&self, mut f: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        // Collect first so no shard lock is held while removing
+        let keys_to_remove: Vec<_> = self
+            .storage
+            .iter()
+            .filter_map(|entry| {
+                if f(entry.key(), &entry.value().value) {
+                    None
+                } else {
+                    Some(Arc::clone(entry.key()))
+                }
+            })
+            .collect();
+        for key in keys_to_remove {
+            self.remove_key(&key);
+        }
+        self.update_cache_stats();
+    }
+
+    /// Calls `f` on every live entry, spreading the work across threads with rayon
+    ///
+    /// Shards are visited in parallel without collecting keys first. Each shard
+    /// is read-locked while its entries are visited, so writes to that shard
+    /// wait until `f` has run over it; keep `f` short or hand work off. Entries
+    /// are not touched and no statistics are recorded.
+    #[cfg(feature = "rayon")]
+    pub fn par_for_each<F>(&self, f: F)
+    where
+        F: Fn(&K, &V) + Sync + Send,
+        K: Send + Sync,
+        V: Send + Sync,
+        S: Send + Sync,
+    {
+        use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+        self.storage
+            .par_iter()
+            .filter(|entry| !self.expired(entry.key(), entry.value()))
+            .for_each(|entry| f(entry.key(), &entry.value().value));
+    }
+
+    /// Removes and returns all live entries, leaving the cache empty
+    ///
+    /// Entries are removed one key at a time, so a value inserted concurrently is
+    /// either returned here or left in the cache, never dropped silently.
+    pub fn drain(&self) -> Vec<(K, V)> {
+        let keys: Vec<Arc<K>> = self
+            .storage
+            .iter()
+            .map(|entry| Arc::clone(entry.key()))
+            .collect();
+        let drained = keys
+            .iter()
+            .filter_map(|key| self.remove_key(key))
+            .filter(|(key, entry)| !self.expired(key, entry))
+            .map(|(key, entry)| (unwrap_key(key), entry.value))
+            .collect();
+        self.update_cache_stats();
+        drained
+    }
+
+    /// Clears all entries in the cache
+    ///
+    /// Hit/miss counters keep accumulating; use `clear_and_reset` to zero them too.
+    pub fn clear(&self) {
+        self.storage.clear();
+        self.bytes.store(0, Ordering::Relaxed);
+        self.update_cache_stats();
+    }
+
+    /// Clears all entries and resets hit/miss counters, windowed rates and latency samples
+    pub fn clear_and_reset(&self) {
+        self.storage.clear();
+        self.bytes.store(0, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.deduped.store(0, Ordering::Relaxed);
+        self.window.reset();
+        self.latency.reset();
+        // Published stats only move forward, so replace them explicitly
+        let name = self.stats_name();
+        let stats = self.current_stats(name.clone(), self.len());
+        get_global_stats().replace(name, self.id, &self.window, stats);
+    }
+
+    /// Returns current number of stored entries, including expired ones not yet removed
+    pub fn len(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Checks whether the cache stores no entries, expired or not
+    pub fn is_empty(&self) -> bool {
+        self.storage.is_empty()
+    }
+
+    /// Returns the number of entries that have not expired
+    ///
+    /// Walks every entry, so it costs more than `len`.
+    pub fn live_len(&self) -> usize {
+        self.storage
+            .iter()
+            .filter(|entry| !self.expired(entry.key(), entry.value()))
+            .count()
+    }
+
+    /// Checks whether the cache holds no live entries
+    pub fn is_truly_empty(&self) -> bool {
+        !self
+            .storage
+            .iter()
+            .any(|entry| !self.expired(entry.key(), entry.value()))
+    }
+
+    /// Returns every stored key from least to most recently used
+    ///
+    /// This is the order LRU eviction removes entries in: by last access, ties
+    /// broken by creation time. Expired entries not yet removed are included.
+    pub fn lru_order(&self) -> Vec<K> {
+        let mut entries = self.recency_snapshot();
+        entries.sort_by(by_recency);
+        entries
+            .into_iter()
+            .map(|(key, _, _)| unwrap_key(key))
+            .collect()
+    }
+
+    /// Returns the cache's current eviction policy
+    ///
+    /// Returned by value because `set_policy` may swap it at any time.
+    pub fn policy(&self) -> EvictionPolicy {
+        self.read_policy().clone()
+    }
+
+    /// Replaces the eviction policy for this cache and its clones
+    ///
+    /// A smaller LRU limit takes effect immediately by evicting down to the new
+    /// size, and `Disabled` evicts everything. A new TTL applies to entries
+    /// inserted from now on.
+    pub fn set_policy(&self, policy: EvictionPolicy) {
+        *self.policy.write().unwrap_or_else(PoisonError::into_inner) = policy;
+        self.maybe_evict();
+        self.update_cache_stats();
+    }
+
+    /// Returns how many locked shards the storage is split into
+    pub fn shard_count(&self) -> usize {
+        self.storage.shards().len()
+    }
+
+    /// Returns the number of stored entries in each shard, for spotting skewed hashing
+    ///
+    /// Shards are read-locked one at a time, so concurrent writes may land
+    /// between reads and the sum need not match a single `len` call.
+    pub fn shard_sizes(&self) -> Vec<usize> {
+        self.storage
+            .shards()
+            .iter()
+            .map(|shard| shard.read().len())
+            .collect()
+    }
+
+    /// Returns the configured entry limit, or `None` for unbounded policies
+    pub fn capacity(&self) -> Option<usize> {
+        match &*self.read_policy() {
+            EvictionPolicy::Lru(limit)
+            | EvictionPolicy::LruTtl { limit, .. }
+            | EvictionPolicy::TinyLfu { limit }
+            | EvictionPolicy::SampledLru { limit, .. } => Some(*limit),
+            EvictionPolicy::Disabled => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Returns how many more entries fit before eviction kicks in, or `None` if unbounded
+    pub fn remaining_capacity(&self) -> Option<usize> {
+        self.capacity()
+            .map(|limit| limit.saturating_sub(self.len()))
+    }
+
+    /// Returns the approximate memory used by all entries, as estimated by the weigher
+    pub fn approx_memory_bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns this cache's current statistics
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            recent_hit_rate: Some(self.windowed_hit_rate(RECENT_HIT_WINDOW)),
+            ..self.current_stats(self.stats_name(), self.live_len())
+        }
+    }
+
+    /// Returns the statistics accumulated since `snapshot`, an earlier `stats()` reading
+    ///
+    /// See `CacheStats::delta`.
+    pub fn stats_since(&self, snapshot: &CacheStats) -> CacheStats {
+        self.stats().delta(snapshot)
+    }
+
+    /// Returns a handle reading the same hit/miss counters as this cache and its clones
+    pub fn counters(&self) -> CacheCounters {
+        CacheCounters {
+            hits: Arc::clone(&self.hits),
+            misses: Arc::clone(&self.misses),
+        }
+    }
+
+    /// Returns number of cache hits
+    pub fn hit_count(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns number of cache misses
+    pub fn miss_count(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many callers found their value already computed after waiting on another's computation
+    ///
+    /// Only `ComputePolicy::Serialized` and `get_async` wait, so this stays zero otherwise.
+    pub fn deduped_count(&self) -> u64 {
+        self.deduped.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many keys have a computation running or callers waiting on one
+    ///
+    /// Tracked for `ComputePolicy::Serialized` and `get_async`; `Concurrent`
+    /// computations are not registered anywhere and are not counted.
+    pub fn inflight_count(&self) -> usize {
+        let pending = self.compute_locks.len();
+        #[cfg(feature = "async")]
+        let pending = pending + self.inflight.len();
+        pending
+    }
+
+    /// Calculates current hit rate as fraction in [0.0, 1.0]
+    pub fn hit_rate(&self) -> f64 {
+        window::rate(self.hit_count(), self.miss_count())
+    }
+
+    /// Calculates current miss rate as fraction in [0.0, 1.0]; 0.0 before any request
+    pub fn miss_rate(&self) -> f64 {
+        window::rate(self.miss_count(), self.hit_count())
+    }
+
+    /// Returns the total number of requests (hits plus misses)
+    pub fn total_requests(&self) -> u64 {
+        self.hit_count() + self.miss_count()
+    }
+
+    /// Returns the hit rate over requests made within the last `window`
+    ///
+    /// Unlike `hit_rate`, a cold start stops weighing on this figure once it
+    /// falls out of the window. Windows are counted in whole seconds and
+    /// clamped to five minutes.
+    pub fn windowed_hit_rate(&self, window: Duration) -> f64 {
+        let (hits, misses) = self.window.counts(window);
+        window::rate(hits, misses)
+    }
+
+    /// Returns percentiles of how long recent misses spent in `compute()`
+    ///
+    /// Covers the last 1024 computations; `None` until the first miss. A batch
+    /// computed by `get_many` counts as one sample.
+    pub fn compute_latency_stats(&self) -> Option<LatencyStats> {
+        self.latency.stats()
+    }
+
+    /// Returns `(hits, misses)` within the last `window`
+    pub(crate) fn windowed_counts(&self, window: Duration) -> (u64, u64) {
+        self.window.counts(window)
+    }
+
+    /// Returns the autotuner generation shared by this cache's handles
+    pub(crate) fn tuner_generation(&self) -> &AtomicU64 {
+        &self.tuner
+    }
+
+    /// Returns a handle for background workers that stops once every strong handle is dropped
+    pub(crate) fn downgrade(&self) -> WeakCache<K, V, S> {
+        WeakCache {
+            storage: Arc::downgrade(&self.storage),
+            settings: Cache {
+                storage: Arc::new(DashMap::with_hasher(self.storage.hasher().clone())),
+                ..self.clone()
+            },
+        }
+    }
+
+    /// Counts `count` hits in the lifetime and windowed totals
+    fn record_hits(&self, count: u64) {
+        self.hits.fetch_add(count, Ordering::Relaxed);
+        self.window.record(count, 0);
+    }
+
+    /// Counts `count` misses in the lifetime and windowed totals
+    fn record_misses(&self, count: u64) {
+        self.misses.fetch_add(count, Ordering::Relaxed);
+        self.window.record(0, count);
+    }
+
+    /// Counts a miss for a call that skipped the cache because caching is globally disabled
+    fn record_bypass(&self) {
+        self.record_misses(1);
+        self.update_cache_stats();
+    }
+
+    /// Shared body of the `insert*` methods, returning the live value replaced
+    fn insert_entry(&self, key: K, entry: CacheEntry<V>) -> Option<V> {
+        let pinned = self.is_pinned(&key);
+        let previous = self.store(key, entry);
+        self.maybe_evict();
+        self.update_cache_stats();
+        previous
+            .filter(|entry| pinned || !entry.is_expired_at(self.clock.now()))
+            .map(|entry| entry.value)
+    }
+
+    /// Stores an explicitly written entry, counting the access towards `TinyLfu`
+    fn store(&self, key: K, entry: CacheEntry<V>) -> Option<CacheEntry<V>> {
+        self.record_access(&key);
+        self.put(key, entry)
+    }
+
+    /// Stores a computed miss, whose lookup in the same call already counted the access
+    ///
+    /// Under `TinyLfu` a new key may be turned away instead, returning `None`.
+    fn store_computed(&self, key: K, entry: CacheEntry<V>) -> Option<CacheEntry<V>> {
+        if !self.admit(&key) {
+            return None;
+        }
+        self.put(key, entry)
+    }
+
+    /// Writes an entry, keeping the memory estimate in step with the replaced value
+    fn put(&self, key: K, entry: CacheEntry<V>) -> Option<CacheEntry<V>> {
+        if *self.read_policy() == EvictionPolicy::Disabled {
+            return None;
+        }
+        let weight = self.weigh(&key, &entry.value);
+        self.bytes.fetch_add(weight, Ordering::Relaxed);
+        let key = Arc::new(key);
+        let previous = self.storage.insert(Arc::clone(&key), entry);
+        if let Some(previous) = &previous {
+            self.release(self.weigh(&key, &previous.value));
+        }
+        previous
+    }
+
+    /// Replaces a refreshed entry's value and restarts its TTL, keeping the memory estimate in step
+    ///
+    /// Does nothing if the key was removed while the refresh ran.
+    fn store_refreshed(&self, key: &K, value: V) {
+        let Some(mut entry) = self.storage.get_mut(key) else {
+            return;
+        };
+        let before = self.weigh(key, &entry.value);
+        let after = self.weigh(key, &value);
+        let now = self.clock.now();
+        entry.value = value;
+        entry.created_at = now;
+        entry.last_accessed = now;
+        drop(entry);
+        self.bytes.fetch_add(after, Ordering::Relaxed);
+        self.release(before);
+        self.update_cache_stats();
+    }
+
+    /// Removes an entry, subtracting its weight from the memory estimate
+    fn remove_key(&self, key: &K) -> Option<(Arc<K>, CacheEntry<V>)> {
+        let removed = self.storage.remove(key)?;
+        self.release(self.weigh(&removed.0, &removed.1.value));
+        Some(removed)
+    }
+
+    /// Estimates one entry's size in bytes
+    fn weigh(&self, key: &K, value: &V) -> u64 {
+        match &self.weigher {
+            Some(weigher) => weigher(key, value),
+            None => (std::mem::size_of::<K>() + std::mem::size_of::<V>()) as u64,
+        }
+    }
+
+    /// Subtracts `weight` from the memory estimate without wrapping below zero
+    fn release(&self, weight: u64) {
+        let _ = self
+            .bytes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bytes| {
+                Some(bytes.saturating_sub(weight))
+            });
+    }
+
+    /// Computes a missing key's entry under the cache's `ComputePolicy`
+    fn compute_missing<F, E>(&self, key: &K, compute: F) -> Result<(V, CacheOutcome), E>
+    where
+        F: FnOnce() -> Result<CacheEntry<V>, E>,
+    {
+        let _computing = Computing::enter(
+            Arc::as_ptr(&self.storage) as usize,
+            self.storage.hasher().hash_one(key),
+            key,
+        );
+        let computed = |compute: F| {
+            self.compute_and_store(key, compute)
+                .map(|value| (value, CacheOutcome::Miss))
+        };
+        match self.compute_policy {
+            ComputePolicy::Concurrent => computed(compute),
+            ComputePolicy::Serialized => self.with_compute_lock(key, || {
+                // Another caller may have stored the value while we waited
+                match self.touch_live(key) {
+                    Some(found) => {
+                        self.deduped.fetch_add(1, Ordering::Relaxed);
+                        self.update_cache_stats();
+                        Ok(found)
+                    }
+                    None => computed(compute),
+                }
+            }),
+        }
+    }
+
+    /// Runs `compute` for a missing key, counting the miss and caching an `Ok` entry
+    fn compute_and_store<F, E>(&self, key: &K, compute: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Result<CacheEntry<V>, E>,
+    {
+        self.record_misses(1);
+        self.trace(key, Traced::Miss);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            target: "fondue",
+            "compute",
+            namespace = %self.stats_name(),
+            key = %self.traced_key(key),
+        )
+        .entered();
+        let started = Instant::now();
+        let result = compute();
+        self.latency.record(started.elapsed());
+        match result {
+            Ok(entry) => {
+                let value = entry.value.clone();
+                self.store_computed(key.clone(), entry);
+                self.maybe_evict();
+                self.update_cache_stats();
+                Ok(value)
+            }
+            Err(err) => {
+                self.update_cache_stats();
+                Err(err)
+            }
+        }
+    }
+
+    /// Runs `f` while holding the per-key compute lock, removing the lock when idle
+    fn with_compute_lock<T>(&self, key: &K, f: impl FnOnce() -> T) -> T {
+        let lock = self
+            .compute_locks
+            .entry(key.clone())
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone();
+        let result = {
+            // A panicking compute poisons the lock but leaves nothing half-written
+            let _guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+            f()
+        };
+        self.compute_locks
+            .remove_if(key, |_, pending| Arc::ptr_eq(pending, &lock));
+        result
+    }
+
+    /// Returns a live entry's value, touching it and counting a hit; expired entries are removed
+    fn lookup(&self, key: &K) -> Option<V> {
+        self.lookup_outcome(key).map(|(value, _)| value)
+    }
+
+    /// `lookup` that also reports whether the hit started a refresh-ahead
+    fn lookup_outcome(&self, key: &K) -> Option<(V, CacheOutcome)> {
+        self.record_access(key);
+        self.touch_live(key)
+    }
+
+    /// `lookup_outcome` for a call whose access was already counted towards `TinyLfu`
+    fn touch_live(&self, key: &K) -> Option<(V, CacheOutcome)> {
+        let mut entry = self.storage.get_mut(key)?;
+        if self.expired(key, &entry) {
+            // Kept for `with_stale_if_error` until recomputed or past `max_stale`
+            let keep = self.is_servable_stale(&entry);
+            drop(entry);
+            if !keep {
+                self.evict_key(key, Traced::Expired);
+            }
+            return None;
+        }
+        let outcome = if self.maybe_refresh(key, &entry) {
+            CacheOutcome::Refreshed
+        } else {
+            CacheOutcome::Hit
+        };
+        entry.touch_at(self.clock.now());
+        let value = entry.value.clone();
+        drop(entry);
+        self.record_hits(1);
+        self.trace(key, Traced::Hit);
+        Some((value, outcome))
+    }
+
+    /// Returns the value of an expired entry that has not been removed yet
+    fn stale_value(&self, key: &K) -> Option<V> {
+        let entry = self.storage.get(key)?;
+        self.expired(key, &entry).then(|| entry.value.clone())
+    }
+
+    /// Returns an expired value still within the `with_stale_if_error` window
+    fn servable_stale_value(&self, key: &K) -> Option<V> {
+        self.stale_if_error?;
+        let entry = self.storage.get(key)?;
+        self.is_servable_stale(&entry).then(|| entry.value.clone())
+    }
+
+    /// Checks whether an expired entry may still be served if recomputing it fails
+    fn is_servable_stale(&self, entry: &CacheEntry<V>) -> bool {
+        match (self.stale_if_error, entry.overdue_at(self.clock.now())) {
+            (Some(max_stale), Some(overdue)) => overdue < max_stale,
+            _ => false,
+        }
+    }
+
+    /// Checks whether an entry has expired; pinned keys never do
+    fn expired(&self, key: &K, entry: &CacheEntry<V>) -> bool {
+        entry.is_expired_at(self.clock.now()) && !self.is_pinned(key)
+    }
+
+    /// Reads a live entry through `f` without updating its access metadata
+    fn read_entry<T>(&self, key: &K, f: impl FnOnce(&CacheEntry<V>) -> T) -> Option<T> {
+        let entry = self.storage.get(key)?;
+        if self.expired(key, &entry) {
+            None
+        } else {
+            Some(f(&entry))
+        }
+    }
+
+    /// Starts a background refresh if refresh-ahead is enabled and the entry is close to expiry
+    fn maybe_refresh(&self, key: &K, entry: &CacheEntry<V>) -> bool {
+        let Some(refresh) = &self.refresh else {
+            return false;
+        };
+        let (Some(ttl), Some(remaining)) = (entry.ttl, entry.ttl_remaining_at(self.clock.now()))
+        else {
+            return false;
+        };
+        if remaining.as_secs_f64() <= ttl.as_secs_f64() * refresh.threshold
+            && refresh.pending.insert(key.clone(), ()).is_none()
+        {
+            (refresh.spawn)(key.clone(), self.downgrade());
+            return true;
+        }
+        false
+    }
+
+    /// Builds an entry for `value` carrying the policy's TTL settings
+    fn new_entry(&self, value: V) -> CacheEntry<V> {
+        let (ttl, ttl_type) = match &*self.read_policy() {
+            EvictionPolicy::Ttl { duration, ttl_type } => (Some(*duration), Some(ttl_type.clone())),
+            EvictionPolicy::LruTtl {
+                duration, ttl_type, ..
+            } => (Some(*duration), Some(ttl_type.clone())),
+            _ => (None, None),
+        };
+        let ttl = ttl.map(|ttl| {
+            if self.jitter > 0.0 {
+                ttl.mul_f64(1.0 + self.jitter * (2.0 * fastrand::f64() - 1.0))
+            } else {
+                ttl
+            }
+        });
+        CacheEntry::new_at(value, ttl, ttl_type, self.clock.now())
+    }
+
+    /// Removes expired entries and evicts based on policy limits if needed
+    ///
+    /// Only `SweepOnWrite` scans for expired entries here; with the other
+    /// strategies a write below the LRU limit does no eviction work at all.
+    fn maybe_evict(&self) {
+        if self.expiry == ExpiryStrategy::SweepOnWrite {
+            self.sweep_expired();
+        }
+        self.enforce_limit();
+    }
+
+    /// Evicts the overflow once the entry count exceeds the LRU limit
+    fn enforce_limit(&self) {
+        // Read the length once; concurrent writers may shrink it between reads
+        let len = self.storage.len();
+        let (limit, sample_size) = match &*self.read_policy() {
+            EvictionPolicy::Lru(limit)
+            | EvictionPolicy::LruTtl { limit, .. }
+            | EvictionPolicy::TinyLfu { limit } => (*limit, None),
+            EvictionPolicy::SampledLru { limit, sample_size } => (*limit, Some(*sample_size)),
+            EvictionPolicy::Disabled => (0, None),
+            _ => return,
+        };
+        if len > limit {
+            let target = (limit as f64 * self.low_watermark).floor() as usize;
+            match sample_size {
+                Some(sample_size) => self.evict_sampled(len - target, sample_size),
+                None => self.evict_lru(len - target),
+            }
+        }
+    }
+
+    /// Locks the policy for reading; a poisoned lock still holds a valid policy
+    fn read_policy(&self) -> std::sync::RwLockReadGuard<'_, EvictionPolicy> {
+        self.policy.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Removes all expired entries, returning how many were removed
+    fn sweep_expired(&self) -> usize {
+        let keys_to_remove: Vec<_> = self
+            .storage
+            .iter()
+            .filter_map(|entry| {
+                if self.expired(entry.key(), entry.value())
+                    && !self.is_servable_stale(entry.value())
+                {
+                    Some(Arc::clone(entry.key()))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        keys_to_remove
+            .iter()
+            .filter(|key| self.evict_key(key, Traced::Expired))
+            .count()
+    }
+
+    /// Runs `f` on the sketch feeding `TinyLfu` admission, if that policy is active
+    ///
+    /// The sketch is sized for the current limit. When `set_policy` or the
+    /// autotuner moves the limit far enough to need a different width, it is
+    /// rebuilt and its counts start over.
+    fn with_sketch<T>(&self, f: impl FnOnce(&mut FrequencySketch) -> T) -> Option<T> {
+        let EvictionPolicy::TinyLfu { limit } = *self.read_policy() else {
+            return None;
+        };
+        let mut sketch = self.sketch.lock().unwrap_or_else(PoisonError::into_inner);
+        if !sketch.as_ref().is_some_and(|sketch| sketch.fits(limit)) {
+            *sketch = Some(FrequencySketch::new(limit));
+        }
+        sketch.as_mut().map(f)
+    }
+
+    /// Counts an access to `key` towards its `TinyLfu` frequency
+    fn record_access(&self, key: &K) {
+        let hash = self.storage.hasher().hash_one(key);
+        self.with_sketch(|sketch| sketch.increment(hash));
+    }
+
+    /// Decides whether `key` may be stored, making room for it if so
+    ///
+    /// Always true except under a full `TinyLfu` cache, where a new key must
+    /// have been accessed more often than the entry it would displace: the
+    /// least recently used of a small sample, as `SampledLru` picks, so that
+    /// admission costs the same however large the cache is. An admitted key
+    /// evicts that entry.
+    fn admit(&self, key: &K) -> bool {
+        if !matches!(*self.read_policy(), EvictionPolicy::TinyLfu { .. }) {
+            return true;
+        }
+        if self.remaining_capacity() != Some(0) || self.storage.contains_key(key) {
+            return true;
+        }
+        let Some(victim) = self.sampled_victim(ADMISSION_SAMPLE) else {
+            return true;
+        };
+        let hasher = self.storage.hasher();
+        let (candidate, incumbent) = (hasher.hash_one(key), hasher.hash_one(&*victim));
+        let admitted = self
+            .with_sketch(|sketch| sketch.estimate(candidate) > sketch.estimate(incumbent))
+            .unwrap_or(true);
+        if admitted {
+            self.evict_key(&victim, Traced::Evicted);
+        }
+        admitted
+    }
+
+    /// Evicts least recently used entries equal to `count`
+    ///
+    /// Selects the `count` oldest entries in linear time rather than sorting
+    /// the whole cache.
+    fn evict_lru(&self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let mut entries = self.recency_snapshot();
+        entries.retain(|(key, _, _)| !self.is_pinned(key));
+        if count < entries.len() {
+            entries.select_nth_unstable_by(count - 1, by_recency);
+            entries.truncate(count);
+        }
+        for (key, _, _) in entries {
+            self.evict_key(&key, Traced::Evicted);
+        }
+    }
+
+    /// Evicts `count` entries, each the least recently used of a fresh random sample
+    fn evict_sampled(&self, count: usize, sample_size: usize) {
+        for _ in 0..count {
+            match self.sampled_victim(sample_size.max(1)) {
+                Some(victim) => {
+                    self.evict_key(&victim, Traced::Evicted);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Picks the least recently used of up to `sample_size` unpinned entries
+    ///
+    /// Reads `sample_size` consecutive entries of a random shard, starting at
+    /// a random position; entries sit in hash order, so that is a random
+    /// sample of keys. Only that shard is read-locked, and nothing is copied
+    /// or sorted, unlike the full scan exact LRU does. Skipping to the start
+    /// walks part of the one shard, a fraction of the cache's size.
+    fn sampled_victim(&self, sample_size: usize) -> Option<Arc<K>> {
+        let older = |a: &CacheEntry<V>, b: &CacheEntry<V>| {
+            a.last_accessed
+                .cmp(&b.last_accessed)
+                .then_with(|| a.created_at.cmp(&b.created_at))
+        };
+        let shards = self.storage.shards();
+        let start = fastrand::usize(..shards.len());
+        for i in 0..shards.len() {
+            let shard = shards[(start + i) % shards.len()].read();
+            if shard.is_empty() {
+                continue;
+            }
+            let offset = fastrand::usize(..shard.len());
+            let victim = shard
+                .iter()
+                .skip(offset)
+                .chain(shard.iter().take(offset))
+                .filter(|(key, _)| !self.is_pinned(key))
+                .take(sample_size)
+                .min_by(|(_, a), (_, b)| older(a.get(), b.get()))
+                .map(|(key, _)| Arc::clone(key));
+            if victim.is_some() {
+                return victim;
+            }
+        }
+        None
+    }
+
+    /// Removes an entry the policy dropped, telling the `on_evict` listener
+    ///
+    /// `reason` is `Evicted` or `Expired`, for the `tracing` event.
+    fn evict_key(&self, key: &K, reason: Traced) -> bool {
+        match self.remove_key(key) {
+            Some((key, entry)) => {
+                self.trace(&key, reason);
+                if let Some(listener) = &self.on_evict {
+                    listener(&key, &entry.value);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Emits a `tracing` event for `key` with this cache's name and `outcome`
+    ///
+    /// Hits and misses are logged at `TRACE`, evictions and expiries at
+    /// `DEBUG`, all under the `fondue` target. Field values are only built
+    /// when a subscriber wants the event. Compiles to nothing without the
+    /// `tracing` feature.
+    #[inline(always)]
+    fn trace(&self, key: &K, outcome: Traced) {
+        #[cfg(feature = "tracing")]
+        {
+            macro_rules! emit {
+                ($level:ident, $outcome:literal) => {
+                    tracing::$level!(
+                        target: "fondue",
+                        namespace = %self.stats_name(),
+                        key = %self.traced_key(key),
+                        outcome = $outcome,
+                    )
+                };
+            }
+            match outcome {
+                Traced::Hit => emit!(trace, "hit"),
+                Traced::Miss => emit!(trace, "miss"),
+                Traced::Evicted => emit!(debug, "evicted"),
+                Traced::Expired => emit!(debug, "expired"),
+            }
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = (key, outcome);
+    }
+
+    /// Renders `key` with the builder's `trace_keys` formatter, or redacts it
+    #[cfg(feature = "tracing")]
+    fn traced_key(&self, key: &K) -> String {
+        match &self.trace_keys {
+            Some(format) => format(key),
+            None => "<redacted>".to_string(),
+        }
+    }
+
+    /// Collects each key with the timestamps LRU ordering compares
+    ///
+    /// Keys are shared with the storage, so large keys are not copied.
+    fn recency_snapshot(&self) -> Vec<(Arc<K>, Instant, Instant)> {
+        self.storage
+            .iter()
+            .map(|entry| {
+                (
+                    Arc::clone(entry.key()),
+                    entry.value().last_accessed,
+                    entry.value().created_at,
+                )
+            })
+            .collect()
+    }
+
+    /// Name this cache's stats are registered under, shared by all its clones
+    ///
+    /// The builder's `name` if one was given, otherwise derived from the storage address.
+    pub(crate) fn stats_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.to_string(),
+            None => format!("Cache@{:p}", Arc::as_ptr(&self.storage)),
+        }
+    }
+
+    /// Removes this cache's row from the global stats registry
+    pub(crate) fn forget_stats(&self) {
+        get_global_stats().remove(&self.stats_name());
+    }
+
+    /// Updates global cache statistics after cache state changes
+    ///
+    /// Hits and misses are each loaded once so the published hit rate agrees
+    /// with the published counts. This runs on every operation, so it reports
+    /// `len` instead of scanning for `live_len`: expired entries count until
+    /// they are removed. `stats()` always reports live entries.
+    fn update_cache_stats(&self) {
+        let name = self.stats_name();
+        let stats = self.current_stats(name.clone(), self.len());
+        record_stats(name, self.id, &self.window, stats);
+    }
+
+    /// Reads the cache's current stats under `name`, reporting `entries` as its size
+    fn current_stats(&self, name: String, entries: usize) -> CacheStats {
+        let hits = self.hit_count();
+        let misses = self.miss_count();
+        CacheStats {
+            name,
+            hits,
+            misses,
+            entries: entries as u64,
+            hit_rate: window::rate(hits, misses),
+            recent_hit_rate: None, // Read from `window` by `stats()` and the registry
+            bytes: self.approx_memory_bytes(),
+            deduped: self.deduped_count(),
+        }
+    }
+}
+
+impl<K, V, S> Default for Cache<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone + Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(EvictionPolicy::None, S::default())
+    }
+}
+
+impl<K, V, S> Clone for Cache<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            storage: Arc::clone(&self.storage),
+            id: self.id,
+            policy: Arc::clone(&self.policy),
+            hits: Arc::clone(&self.hits),
+            misses: Arc::clone(&self.misses),
+            deduped: Arc::clone(&self.deduped),
+            jitter: self.jitter,
+            low_watermark: self.low_watermark,
+            expiry: self.expiry,
+            compute_policy: self.compute_policy,
+            compute_locks: Arc::clone(&self.compute_locks),
+            refresh: self.refresh.clone(),
+            bytes: Arc::clone(&self.bytes),
+            window: Arc::clone(&self.window),
+            latency: Arc::clone(&self.latency),
+            weigher: self.weigher.clone(),
+            stale_if_error: self.stale_if_error,
+            sketch: Arc::clone(&self.sketch),
+            name: self.name.clone(),
+            on_evict: self.on_evict.clone(),
+            pinned: Arc::clone(&self.pinned),
+            clock: Arc::clone(&self.clock),
+            tuner: Arc::clone(&self.tuner),
+            #[cfg(feature = "async")]
+            inflight: Arc::clone(&self.inflight),
+            #[cfg(feature = "tracing")]
+            trace_keys: self.trace_keys.clone(),
+        }
+    }
+}
+
+// --- GLOBAL CACHE STORAGE ---
+
+/// Global thread-safe registry of caches, one per namespace
+static GLOBAL_CACHE_STORAGE: OnceLock<Arc<DashMap<String, Cache<String, String>>>> =
+    OnceLock::new();
+
+/// Returns global cache storage singleton
+pub(crate) fn get_global_cache_storage() -> &'static Arc<DashMap<String, Cache<String, String>>> {
+    GLOBAL_CACHE_STORAGE.get_or_init(|| Arc::new(DashMap::new()))
+}
+
+/// Decides whether a key may be stored in the global caches
+type KeyValidator = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Validator applied to keys passed to the global `cache_*` functions
+static KEY_VALIDATOR: RwLock<Option<KeyValidator>> = RwLock::new(None);
+
+/// Rejects global cache keys for which `validator` returns false
+///
+/// Calls with a rejected key run `compute` directly and store nothing, so
+/// untrusted input cannot grow the global caches with arbitrary keys. Replaces
+/// any previously set validator.
+pub fn cache_set_key_validator<F>(validator: F)
+where
+    F: Fn(&str) -> bool + Send + Sync + 'static,
+{
+    *KEY_VALIDATOR.write().unwrap() = Some(Arc::new(validator));
+}
+
+/// Rejects global cache keys longer than `max_len` bytes; see `cache_set_key_validator`
+pub fn cache_set_max_key_len(max_len: usize) {
+    cache_set_key_validator(move |key| key.len() <= max_len);
+}
+
+/// Removes the key validator so every key is cached again
+pub fn cache_clear_key_validator() {
+    *KEY_VALIDATOR.write().unwrap() = None;
+}
+
+/// TTL given to namespaces created without one; see `set_default_ttl`
+static DEFAULT_TTL: RwLock<Option<(Duration, TtlType)>> = RwLock::new(None);
+
+/// Sets the TTL that plain `cache_get` and other no-eviction namespaces adopt
+///
+/// A namespace created with `EvictionPolicy::None` while a default is set
+/// gets `EvictionPolicy::Ttl` with it instead. The policy is fixed when the
+/// namespace is created, so set this at startup; namespaces that already
+/// exist keep theirs. `None` restores unbounded lifetimes for new namespaces.
+pub fn set_default_ttl(ttl: Option<(Duration, TtlType)>) {
+    *DEFAULT_TTL.write().unwrap() = ttl;
+}
+
+/// Returns the TTL set by `set_default_ttl`, if any
+pub fn default_ttl() -> Option<(Duration, TtlType)> {
+    DEFAULT_TTL.read().unwrap().clone()
+}
+
+/// Whether the global `cache_*` functions use their caches; see `set_caching_enabled`
+static CACHING_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turns caching by the global `cache_*` functions and macros on or off
+///
+/// While disabled, every call runs `compute` and counts a miss in its
+/// namespace's stats, but nothing is read from or stored in the caches, so the
+/// cost of running without a cache can be measured in place. Entries cached
+/// before disabling are kept and served again once re-enabled. `Cache` values
+/// created directly are unaffected; give those `EvictionPolicy::Disabled`.
+pub fn set_caching_enabled(enabled: bool) {
+    CACHING_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns false while `set_caching_enabled(false)` is in effect
+pub fn caching_enabled() -> bool {
+    CACHING_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Counts a bypassed call against the namespace when caching is globally disabled
+fn bypassed(namespace: &str, policy: EvictionPolicy) -> bool {
+    if caching_enabled() {
+        return false;
+    }
+    get_or_create_cache(namespace, policy).record_bypass();
+    true
+}
+
+/// Returns true if no validator is set or the validator accepts `key`
+fn key_allowed(key: &str) -> bool {
+    let validator = KEY_VALIDATOR.read().unwrap().clone();
+    validator.map_or(true, |validator| validator(key))
+}
+
+/// Retrieves the namespace's cache, creating it with `policy` if this is its first use
+///
+/// A namespace has exactly one cache. Its policy is fixed by
+/// `cache_configure_namespace` or, failing that, by the first call that uses
+/// the namespace; the policy implied by later calls is ignored so every
+/// `cache_*` function sees the same entries.
+fn get_or_create_cache(namespace: &str, policy: EvictionPolicy) -> Cache<String, String> {
+    let caches = get_global_cache_storage();
+    if let Some(cache) = caches.get(namespace) {
+        return cache.clone();
+    }
+    caches
+        .entry(namespace.to_string())
+        .or_insert_with(|| Cache::with_policy(with_default_ttl(policy)))
+        .clone()
+}
+
+/// Replaces `EvictionPolicy::None` with a TTL policy when a default TTL is set
+fn with_default_ttl(policy: EvictionPolicy) -> EvictionPolicy {
+    match (policy, default_ttl()) {
+        (EvictionPolicy::None, Some((duration, ttl_type))) => {
+            EvictionPolicy::Ttl { duration, ttl_type }
+        }
+        (policy, _) => policy,
+    }
+}
+
+/// Sets the eviction policy for a namespace in the global registry
+///
+/// Call this before the namespace is first used. If the namespace already
+/// exists with a different policy its cache is replaced, dropping its entries.
+pub fn cache_configure_namespace(namespace: &str, policy: EvictionPolicy) {
+    let caches = get_global_cache_storage();
+    let mut cache = caches
+        .entry(namespace.to_string())
+        .or_insert_with(|| Cache::with_policy(policy.clone()));
+    if cache.policy() != policy {
+        std::mem::replace(&mut *cache, Cache::with_policy(policy)).forget_stats();
+    }
+}
+
+/// Returns the policy a namespace's global cache was created with, if it exists
+pub fn cache_namespace_policy(namespace: &str) -> Option<EvictionPolicy> {
+    let caches = get_global_cache_storage();
+    caches.get(namespace).map(|cache| cache.policy())
+}
+
+// --- Cache API functions ---
+
+pub fn cache_get<F, V>(namespace: &str, key: &str, compute: F) -> V
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    cache_get_with_policy(namespace, key, EvictionPolicy::None, compute)
+}
+
+pub fn cache_get_with_ttl<F, V>(
+    namespace: &str,
+    key: &str,
+    ttl: Duration,
+    ttl_type: TtlType,
+    compute: F,
+) -> V
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    let policy = EvictionPolicy::Ttl {
+        duration: ttl,
+        ttl_type,
+    };
+    cache_get_with_policy(namespace, key, policy, compute)
+}
+
+pub fn cache_get_with_limit<F, V>(namespace: &str, key: &str, limit: usize, compute: F) -> V
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    cache_get_with_policy(namespace, key, EvictionPolicy::Lru(limit), compute)
+}
+
+pub fn cache_get_with_ttl_and_limit<F, V>(
+    namespace: &str,
+    key: &str,
+    ttl: Duration,
+    limit: usize,
+    ttl_type: TtlType,
+    compute: F,
+) -> V
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    let policy = EvictionPolicy::LruTtl {
+        limit,
+        duration: ttl,
+        ttl_type,
+    };
+    cache_get_with_policy(namespace, key, policy, compute)
+}
+
+/// Shared body of the `cache_get*` functions
+fn cache_get_with_policy<F, V>(namespace: &str, key: &str, policy: EvictionPolicy, compute: F) -> V
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    if !key_allowed(key) || bypassed(namespace, policy.clone()) {
+        return compute();
+    }
+    let cache = get_or_create_cache(namespace, policy);
+    let cached_value = cache.get(&key.to_string(), || compute().to_string());
+    cached_value
+        .parse::<V>()
+        .expect("Failed to parse cached value")
+}
+
+#[cfg(feature = "async")]
+pub async fn cache_get_async<F, Fut, V>(namespace: &str, key: &str, compute: F) -> V
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = V>,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    if !key_allowed(key) || bypassed(namespace, EvictionPolicy::None) {
+        return compute().await;
+    }
+    let cache = get_or_create_cache(namespace, EvictionPolicy::None);
+    let cached_value = cache
+        .get_async(&key.to_string(), || async { compute().await.to_string() })
+        .await;
+    cached_value
+        .parse::<V>()
+        .expect("Failed to parse cached value")
+}
+
+/// Like `cache_get`, but handles an unparseable cached value according to `on_error`
+///
+/// A value computed by this call is returned as is, even if its string form
+/// would not parse back.
+pub fn cache_get_or_recover<F, V>(
+    namespace: &str,
+    key: &str,
+    on_error: ParseErrorPolicy,
+    compute: F,
+) -> V
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr + Default,
+    V::Err: std::fmt::Debug,
+{
+    if !key_allowed(key) || bypassed(namespace, EvictionPolicy::None) {
+        return compute();
+    }
+    let cache = get_or_create_cache(namespace, EvictionPolicy::None);
+    get_parsed(&cache, &key.to_string(), on_error, compute)
+}
+
+/// Reads `key` through `cache` and parses it, recovering from bad entries per `on_error`
+pub(crate) fn get_parsed<K, F, V>(
+    cache: &Cache<K, String>,
+    key: &K,
+    on_error: ParseErrorPolicy,
+    compute: F,
+) -> V
+where
+    K: Hash + Eq + Clone,
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr + Default,
+    V::Err: std::fmt::Debug,
+{
+    let mut compute = Some(compute);
+    let mut fresh = None;
+    let cached_value = cache.get(key, || {
+        let value = compute.take().expect("compute runs at most once")();
+        let text = value.to_string();
+        fresh = Some(value);
+        text
+    });
+    let err = match cached_value.parse::<V>() {
+        Ok(value) => return value,
+        Err(err) => err,
+    };
+    if let Some(value) = fresh {
+        return value;
+    }
+    match on_error {
+        ParseErrorPolicy::Panic => panic!("Failed to parse cached value: {:?}", err),
+        ParseErrorPolicy::ReturnDefault => V::default(),
+        ParseErrorPolicy::Recompute => {
+            cache.invalidate(key);
+            let value = compute.take().expect("compute not yet run")();
+            cache.get(key, || value.to_string());
+            value
         }
     }
-    invalidated
+}
+
+/// Preloads entries into the namespace's default (no eviction) cache
+pub fn cache_warm<I, V>(namespace: &str, entries: I)
+where
+    I: IntoIterator<Item = (String, V)>,
+    V: ToString,
+{
+    if !caching_enabled() {
+        return;
+    }
+    let cache = get_or_create_cache(namespace, EvictionPolicy::None);
+    cache.warm(
+        entries
+            .into_iter()
+            .filter(|(key, _)| key_allowed(key))
+            .map(|(key, value)| (key, value.to_string())),
+    );
+}
+
+/// Fallible `cache_get`: returns an error instead of panicking if the cached value can't be parsed
+pub fn try_cache_get<F, V>(namespace: &str, key: &str, compute: F) -> Result<V, CacheError>
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    try_cache_get_with_policy(namespace, key, EvictionPolicy::None, compute)
+}
+
+/// Fallible `cache_get_with_ttl`
+pub fn try_cache_get_with_ttl<F, V>(
+    namespace: &str,
+    key: &str,
+    ttl: Duration,
+    ttl_type: TtlType,
+    compute: F,
+) -> Result<V, CacheError>
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    let policy = EvictionPolicy::Ttl {
+        duration: ttl,
+        ttl_type,
+    };
+    try_cache_get_with_policy(namespace, key, policy, compute)
+}
+
+/// Fallible `cache_get_with_limit`
+pub fn try_cache_get_with_limit<F, V>(
+    namespace: &str,
+    key: &str,
+    limit: usize,
+    compute: F,
+) -> Result<V, CacheError>
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    try_cache_get_with_policy(namespace, key, EvictionPolicy::Lru(limit), compute)
+}
+
+/// Fallible `cache_get_with_ttl_and_limit`
+pub fn try_cache_get_with_ttl_and_limit<F, V>(
+    namespace: &str,
+    key: &str,
+    ttl: Duration,
+    limit: usize,
+    ttl_type: TtlType,
+    compute: F,
+) -> Result<V, CacheError>
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    let policy = EvictionPolicy::LruTtl {
+        limit,
+        duration: ttl,
+        ttl_type,
+    };
+    try_cache_get_with_policy(namespace, key, policy, compute)
+}
+
+/// Shared body of the `try_cache_get*` functions
+fn try_cache_get_with_policy<F, V>(
+    namespace: &str,
+    key: &str,
+    policy: EvictionPolicy,
+    compute: F,
+) -> Result<V, CacheError>
+where
+    F: FnOnce() -> V,
+    V: Clone + ToString + std::str::FromStr,
+    V::Err: std::fmt::Debug,
+{
+    if !key_allowed(key) || bypassed(namespace, policy.clone()) {
+        return Ok(compute());
+    }
+    let cache = get_or_create_cache(namespace, policy);
+    let cached_value = cache.get(&key.to_string(), || compute().to_string());
+    cached_value
+        .parse::<V>()
+        .map_err(|err| CacheError::InvalidValue {
+            reason: format!("{:?}", err),
+            value: cached_value,
+        })
+}
+
+/// Joins key components with ':' after escaping '\\' and ':' so distinct argument lists never collide
+#[doc(hidden)]
+pub fn memoize_key(parts: &[String]) -> String {
+    parts
+        .iter()
+        .map(|part| part.replace('\\', "\\\\").replace(':', "\\:"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Invalidate entry by key in the given namespace
+pub fn cache_invalidate(namespace: &str, key: &str) -> bool {
+    namespace_cache(namespace).is_some_and(|cache| cache.invalidate(&key.to_string()))
+}
+
+/// Invalidate `key` in every namespace, returning how many entries were removed
+pub fn cache_invalidate_key_global(key: &str) -> usize {
+    CacheRegistry::global().invalidate_key(key)
+}
+
+/// Invalidate every key starting with `key_prefix` in a namespace, returning how many were removed
+pub fn cache_invalidate_prefix(namespace: &str, key_prefix: &str) -> usize {
+    namespace_cache(namespace).map_or(0, |cache| cache.invalidate_prefix(key_prefix))
+}
+
+/// Lists the namespaces in the global registry, sorted
+pub fn cache_namespaces() -> Vec<String> {
+    CacheRegistry::global().namespaces()
+}
+
+/// Summarizes the stats of a namespace's global cache
+pub fn cache_stats_for_namespace(namespace: &str) -> CacheStats {
+    CacheRegistry::global().namespace_stats(namespace)
+}
+
+/// Returns the hits and misses of a namespace's global cache; `(0, 0)` if it doesn't exist
+///
+/// Matches the namespace exactly, so `"user"` does not include `"users"`.
+pub fn namespace_counts(namespace: &str) -> (u64, u64) {
+    namespace_cache(namespace).map_or((0, 0), |cache| (cache.hit_count(), cache.miss_count()))
+}
+
+/// Returns the lifetime hit rate of a namespace's global cache; 0.0 if it doesn't exist
+pub fn namespace_hit_rate(namespace: &str) -> f64 {
+    let (hits, misses) = namespace_counts(namespace);
+    window::rate(hits, misses)
 }
 
 /// Clear all caches globally
 pub fn cache_clear_all() {
-    let caches = get_global_cache_storage();
-    for cache in caches.iter() {
-        cache.value().clear();
-    }
+    CacheRegistry::global().clear_all();
 }
 
-/// Clear all caches within a given namespace
+/// Clear all entries within a given namespace
 pub fn cache_clear_namespace(namespace: &str) {
-    let caches = get_global_cache_storage();
-    for cache in caches.iter() {
-        if cache.key().starts_with(namespace) {
-            cache.value().clear();
+    if let Some(cache) = namespace_cache(namespace) {
+        cache.clear();
+    }
+}
+
+/// Resets all global state: namespaces, registered stats, the key validator and default TTL
+///
+/// Unlike `cache_clear_all`, which keeps namespaces and their counters, this
+/// leaves the process as if no global cache had been used. Tests in one binary
+/// run in parallel, so call it only where no other test uses the globals, or
+/// use an isolated `CacheRegistry` instead.
+#[cfg(feature = "test-util")]
+pub fn reset_global_state() {
+    get_global_cache_storage().clear();
+    crate::stats::clear_stats();
+    cache_clear_key_validator();
+    set_caching_enabled(true);
+    set_default_ttl(None);
+}
+
+/// Returns a namespace's global cache without creating it
+fn namespace_cache(namespace: &str) -> Option<Cache<String, String>> {
+    CacheRegistry::global().get(namespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_or_try_does_not_cache_errors() {
+        let cache: Cache<String, u64> = Cache::new();
+        let key = "key".to_string();
+        let failed: Result<u64, &str> = cache.get_or_try(&key, || Err("unavailable"));
+        assert_eq!(failed, Err("unavailable"));
+        assert!(cache.is_empty());
+        assert_eq!(cache.miss_count(), 1);
+
+        assert_eq!(cache.get_or_try::<_, &str>(&key, || Ok(7)), Ok(7));
+        assert_eq!(cache.get_or_try::<_, &str>(&key, || Err("unused")), Ok(7));
+        assert_eq!(cache.miss_count(), 2);
+        assert_eq!(cache.hit_count(), 1);
+    }
+
+    #[test]
+    fn test_get_many_computes_missing_in_one_call() {
+        let cache: Cache<u32, u32> = Cache::new();
+        cache.insert_many([(1, 10), (2, 20)]);
+        let mut calls = 0;
+        let results = cache.get_many(&[1, 3, 2, 4, 3], |missing| {
+            calls += 1;
+            assert_eq!(missing, &[3, 4]);
+            vec![(3, 30), (1, 99), (5, 50)]
+        });
+        assert_eq!(calls, 1);
+        assert_eq!(
+            results,
+            vec![
+                Some(Fetched::Hit(10)),
+                Some(Fetched::Computed(30)),
+                Some(Fetched::Hit(20)),
+                None,
+                Some(Fetched::Computed(30)),
+            ]
+        );
+        // Pairs for keys that weren't missing are not cached
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.peek(&1), Some(10));
+        assert_eq!(cache.peek(&5), None);
+        assert_eq!(cache.hit_count(), 2);
+        assert_eq!(cache.miss_count(), 2);
+    }
+
+    #[test]
+    fn test_drain_returns_live_entries() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::Ttl {
+            duration: Duration::from_millis(20),
+            ttl_type: TtlType::Fixed,
+        });
+        cache.insert(1, 10);
+        std::thread::sleep(Duration::from_millis(30));
+        cache.insert(2, 20);
+        let drained = cache.drain();
+        assert_eq!(drained, vec![(2, 20)]);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_jitter_spreads_ttl_within_bounds() {
+        let ttl = Duration::from_secs(60);
+        let cache: Cache<u32, u32> = Cache::with_policy_jitter(
+            EvictionPolicy::Ttl {
+                duration: ttl,
+                ttl_type: TtlType::Fixed,
+            },
+            0.1,
+        );
+        cache.insert_many((0..100).map(|i| (i, i)));
+        let ttls: Vec<Duration> = cache
+            .storage
+            .iter()
+            .map(|entry| entry.value().ttl.unwrap())
+            .collect();
+        assert!(ttls
+            .iter()
+            .all(|t| *t >= Duration::from_secs(54) && *t <= Duration::from_secs(66)));
+        assert!(ttls.iter().any(|t| *t != ttl));
+    }
+
+    #[test]
+    fn test_refresh_ahead_replaces_value_before_expiry() {
+        let version = Arc::new(std::sync::atomic::AtomicU64::new(1));
+        let source = Arc::clone(&version);
+        let cache: Cache<u32, u64> = Cache::with_refresh_ahead(
+            EvictionPolicy::Ttl {
+                duration: Duration::from_millis(200),
+                ttl_type: TtlType::Fixed,
+            },
+            0.5,
+            move |_| source.load(Ordering::SeqCst),
+        );
+        assert_eq!(cache.get(&1, || 1), 1);
+        version.store(2, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(120));
+        // Within the refresh window: the stale value is served while a refresh runs
+        assert_eq!(cache.get(&1, || 0), 1);
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get(&1, || 0), 2);
+        assert_eq!(cache.miss_count(), 1);
+    }
+
+    #[test]
+    fn test_refresh_ahead_reweighs_refreshed_values() {
+        let cache: Cache<u32, String> = Cache {
+            weigher: Some(Arc::new(|_: &u32, value: &String| value.len() as u64)),
+            ..Cache::with_refresh_ahead(
+                EvictionPolicy::Ttl {
+                    duration: Duration::from_millis(100),
+                    ttl_type: TtlType::Fixed,
+                },
+                0.5,
+                |_| "refreshed".to_string(),
+            )
+        };
+        cache.insert(1, "v1".to_string());
+        assert_eq!(cache.approx_memory_bytes(), 2);
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(&1, String::new), "v1");
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(cache.peek(&1).as_deref(), Some("refreshed"));
+        assert_eq!(cache.approx_memory_bytes(), 9);
+    }
+
+    #[test]
+    fn test_try_macros_surface_errors() {
+        let invalid: Result<u32, CacheError> =
+            crate::try_cache_with_ttl!("try_macros", "ttl", "5 parsecs", TtlType::Fixed, || 1);
+        assert!(matches!(invalid, Err(CacheError::InvalidTtl(_))));
+
+        let word: Result<String, CacheError> =
+            crate::try_cache!("try_macros", "word", || "abc".to_string());
+        assert_eq!(word, Ok("abc".to_string()));
+        let number: Result<u32, CacheError> = crate::try_cache!("try_macros", "word", || 0);
+        assert!(matches!(number, Err(CacheError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn test_cache_get_or_recover_policies() {
+        cache_warm("parse_policy", [("n".to_string(), "abc")]);
+        let default: u32 =
+            cache_get_or_recover("parse_policy", "n", ParseErrorPolicy::ReturnDefault, || 7);
+        assert_eq!(default, 0);
+        let recomputed: u32 =
+            cache_get_or_recover("parse_policy", "n", ParseErrorPolicy::Recompute, || 7);
+        assert_eq!(recomputed, 7);
+        assert_eq!(cache_get("parse_policy", "n", || 0u32), 7);
+        let panicked = std::panic::catch_unwind(|| {
+            cache_warm("parse_policy", [("n".to_string(), "abc")]);
+            cache_get_or_recover("parse_policy", "n", ParseErrorPolicy::Panic, || 7u32)
+        });
+        assert!(panicked.is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid TTL \"5 parsecs\": unknown time unit 'parsecs'")]
+    fn test_ttl_macro_panic_names_the_ttl() {
+        let _: u32 = crate::cache_with_ttl!("ttl_panic", "k", "5 parsecs", TtlType::Fixed, || 1);
+    }
+
+    #[test]
+    fn test_cache_key_macro_escapes_separators() {
+        assert_eq!(crate::cache_key!("user", 42, 'x'), "user:42:x");
+        assert_ne!(crate::cache_key!("a:b", "c"), crate::cache_key!("a", "b:c"));
+        assert_eq!(crate::cache_key!("a\\", ":"), "a\\\\:\\:");
+    }
+
+    #[test]
+    fn test_cache_warm_macro() {
+        crate::cache_warm!("warm_macro", { "a" => 1, "b" => 2 });
+        assert_eq!(cache_get("warm_macro", "a", || 0), 1);
+        assert_eq!(cache_get("warm_macro", "b", || 0), 2);
+    }
+
+    #[test]
+    fn test_weigher_tracks_memory() {
+        let cache: Cache<u32, String> =
+            Cache::with_weigher(EvictionPolicy::Lru(2), |_, value: &String| {
+                value.len() as u64
+            });
+        cache.insert(1, "abcd".to_string());
+        cache.insert(2, "ef".to_string());
+        assert_eq!(cache.approx_memory_bytes(), 6);
+        cache.insert(2, "efgh".to_string());
+        assert_eq!(cache.approx_memory_bytes(), 8);
+        cache.insert(3, "i".to_string());
+        assert_eq!(cache.approx_memory_bytes(), 5);
+        cache.invalidate(&3);
+        assert_eq!(cache.approx_memory_bytes(), 4);
+        cache.clear();
+        assert_eq!(cache.approx_memory_bytes(), 0);
+    }
+
+    #[test]
+    fn test_windowed_hit_rate_counts_recent_requests() {
+        let cache: Cache<u32, u32> = Cache::new();
+        cache.get(&1, || 1);
+        cache.get(&1, || 1);
+        assert_eq!(cache.hit_rate(), 0.5);
+        assert_eq!(cache.windowed_hit_rate(Duration::from_secs(10)), 0.5);
+        assert_eq!(cache.windowed_hit_rate(Duration::ZERO), 0.5);
+        // Published stats read the window when queried, not on every operation
+        let published = get_global_stats().get(&cache.stats_name()).unwrap();
+        assert_eq!(published.recent_hit_rate, Some(0.5));
+        cache.get(&1, || 1);
+        let published = get_global_stats().get(&cache.stats_name()).unwrap();
+        assert_eq!(published.recent_hit_rate, Some(2.0 / 3.0));
+    }
+
+    #[test]
+    fn test_pinned_keys_survive_eviction_and_expiry() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::LruTtl {
+            limit: 2,
+            duration: Duration::from_millis(20),
+            ttl_type: TtlType::Fixed,
+        });
+        cache.pin(&1);
+        cache.insert(1, 1);
+        cache.insert(2, 2);
+        cache.insert(3, 3);
+        assert_eq!(cache.keys().len(), 2);
+        assert!(cache.contains_key(&1) && !cache.contains_key(&2));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.get(&1, || unreachable!()), 1);
+        // The pin outlives the entry it protected
+        cache.invalidate(&1);
+        cache.insert(1, 10);
+        assert!(cache.is_pinned(&1));
+
+        assert!(cache.unpin(&1));
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.peek(&1), None);
+    }
+
+    #[test]
+    fn test_expire_now_keeps_entry_until_next_get_or_sweep() {
+        let cache: Cache<u32, u32> =
+            Cache::with_expiry(EvictionPolicy::None, ExpiryStrategy::OnAccessOnly);
+        cache.insert(1, 1);
+        cache.get(&1, || unreachable!());
+        assert!(cache.expire_now(&1));
+        assert!(!cache.expire_now(&2));
+        assert_eq!((cache.len(), cache.peek(&1)), (1, None));
+        assert_eq!(cache.lru_order(), [1]);
+        assert_eq!(cache.get(&1, || 5), 5);
+        assert_eq!(cache.miss_count(), 1);
+
+        // The default strategy sweeps it away on the next write
+        let cache: Cache<u32, u32> = Cache::new();
+        cache.insert(1, 1);
+        assert!(cache.expire_now(&1));
+        assert_eq!(cache.len(), 1);
+        cache.insert(2, 2);
+        assert_eq!((cache.len(), cache.peek(&1)), (1, None));
+        assert_eq!(cache.get(&1, || 5), 5);
+    }
+
+    #[test]
+    fn test_get_cacheable_skips_declined_values() {
+        let cache: Cache<u32, u32> = Cache::new();
+        assert_eq!(cache.get_cacheable(&1, || (0, false)), 0);
+        assert!(cache.is_empty());
+        assert_eq!(cache.get_cacheable(&1, || (5, true)), 5);
+        assert_eq!(cache.get_cacheable(&1, || unreachable!()), 5);
+        assert_eq!((cache.hit_count(), cache.miss_count()), (1, 2));
+    }
+
+    #[test]
+    fn test_get_versioned_recomputes_on_new_version() {
+        let cache: Cache<u32, u32> = Cache::new();
+        assert_eq!(cache.get_versioned(&1, 1, || 10), 10);
+        assert_eq!(cache.get_versioned(&1, 1, || unreachable!()), 10);
+        assert_eq!(cache.get_versioned(&1, 2, || 20), 20);
+        assert_eq!(cache.get_versioned(&1, 2, || unreachable!()), 20);
+        // Unversioned entries never match a version
+        cache.insert(1, 30);
+        assert_eq!(cache.get_versioned(&1, 2, || 40), 40);
+        assert_eq!((cache.hit_count(), cache.miss_count()), (2, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "cyclic cache computation")]
+    fn test_recursive_compute_of_same_key_panics() {
+        let cache: Cache<u32, u32> =
+            Cache::with_compute_policy(EvictionPolicy::None, ComputePolicy::Serialized);
+        assert_eq!(cache.get(&1, || cache.get(&2, || 1) + 1), 2);
+        cache.get(&3, || cache.get(&3, || 0) + 1);
+    }
+
+    #[test]
+    fn test_nested_compute_of_colliding_keys_is_not_cyclic() {
+        #[derive(Clone, Default)]
+        struct Constant;
+        impl BuildHasher for Constant {
+            type Hasher = ConstantHasher;
+            fn build_hasher(&self) -> ConstantHasher {
+                ConstantHasher
+            }
+        }
+        struct ConstantHasher;
+        impl std::hash::Hasher for ConstantHasher {
+            fn finish(&self) -> u64 {
+                0
+            }
+            fn write(&mut self, _: &[u8]) {}
+        }
+
+        let cache: Cache<u32, u32, Constant> = Cache::with_hasher(EvictionPolicy::None, Constant);
+        let inner = cache.clone();
+        assert_eq!(cache.get(&1, || inner.get(&2, || 1) + 1), 2);
+        assert_eq!((cache.get(&1, || 0), cache.get(&2, || 0)), (2, 1));
+    }
+
+    #[test]
+    fn test_age_histogram_buckets_live_entries() {
+        let cache: Cache<u32, u32> = Cache::new();
+        cache.insert(1, 1);
+        cache.insert_with_ttl(2, 2, Duration::from_millis(10), TtlType::Fixed);
+        std::thread::sleep(Duration::from_millis(30));
+        cache.insert(3, 3);
+        let buckets = [Duration::from_millis(20), Duration::from_secs(60)];
+        assert_eq!(cache.age_histogram(&buckets), [1, 1, 0]);
+        assert_eq!(cache.age_histogram(&[]), [2]);
+    }
+
+    #[test]
+    fn test_sampled_lru_hit_rate_close_to_exact_lru() {
+        let run = |policy: EvictionPolicy| {
+            let cache: Cache<u32, u32> = Cache::with_policy(policy);
+            // 90% of requests go to 50 hot keys, the rest to 10,000 cold ones
+            let mut rng = fastrand::Rng::with_seed(7);
+            for _ in 0..20_000 {
+                let key = if rng.u8(..10) < 9 {
+                    rng.u32(..50)
+                } else {
+                    50 + rng.u32(..10_000)
+                };
+                cache.get(&key, || key);
+            }
+            assert!(cache.len() <= 100);
+            cache.hit_rate()
+        };
+        let exact = run(EvictionPolicy::Lru(100));
+        let sampled = run(EvictionPolicy::SampledLru {
+            limit: 100,
+            sample_size: 5,
+        });
+        assert!(exact > 0.8, "exact LRU hit rate {}", exact);
+        assert!(
+            sampled > exact - 0.05,
+            "sampled {} vs exact {}",
+            sampled,
+            exact
+        );
+    }
+
+    #[test]
+    fn test_top_keys_orders_by_access_count() {
+        let cache: Cache<u32, u32> = Cache::new();
+        for (key, reads) in [(1, 1), (2, 4), (3, 2), (4, 0)] {
+            cache.insert(key, key);
+            for _ in 0..reads {
+                cache.get(&key, || unreachable!());
+            }
+        }
+        assert_eq!(cache.top_keys(2), [(2, 4), (3, 2)]);
+        assert_eq!(cache.top_keys(10).len(), 4);
+        assert!(cache.top_keys(0).is_empty());
+    }
+
+    #[test]
+    fn test_stats_since_reports_the_interval() {
+        let cache: Cache<u32, u32> = Cache::new();
+        cache.get(&1, || 1);
+        let before = cache.stats();
+        cache.get(&1, || 1);
+        cache.get(&1, || 1);
+        cache.get(&2, || 2);
+        let interval = cache.stats_since(&before);
+        assert_eq!(
+            (interval.hits, interval.misses, interval.entries),
+            (2, 1, 2)
+        );
+        assert!((interval.hit_rate - 2.0 / 3.0).abs() < 1e-9);
+
+        cache.clear_and_reset();
+        let reset = cache.stats_since(&before);
+        assert_eq!((reset.hits, reset.misses, reset.hit_rate), (0, 0, 0.0));
+    }
+
+    #[test]
+    fn test_miss_rate_and_total_requests() {
+        let cache: Cache<u32, u32> = Cache::new();
+        assert_eq!((cache.total_requests(), cache.miss_rate()), (0, 0.0));
+        for key in [1, 1, 1, 2] {
+            cache.get(&key, || key);
+        }
+        assert_eq!(cache.total_requests(), 4);
+        assert_eq!(cache.miss_rate(), 0.5);
+        assert_eq!(cache.hit_rate() + cache.miss_rate(), 1.0);
+    }
+
+    #[test]
+    fn test_compute_latency_recorded_on_misses_only() {
+        let cache: Cache<u32, u32> = Cache::new();
+        assert!(cache.compute_latency_stats().is_none());
+        cache.get(&1, || {
+            std::thread::sleep(Duration::from_millis(5));
+            1
+        });
+        cache.get(&1, || unreachable!());
+        let stats = cache.compute_latency_stats().unwrap();
+        assert_eq!(stats.samples, 1);
+        assert!(stats.p50 >= Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_stats_snapshot_rows_stay_coherent_under_contention() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::Lru(8));
+        let name = cache.stats_name();
+        // Take over the row from any dropped cache that had the same address
+        cache.clear();
+        let workers: Vec<_> = (0..4)
+            .map(|t| {
+                let cache = cache.clone();
+                std::thread::spawn(move || {
+                    for i in 0..2_000u32 {
+                        cache.get(&((i * 7 + t) % 16), || i);
+                    }
+                })
+            })
+            .collect();
+        let mut last_total = 0;
+        while workers.iter().any(|worker| !worker.is_finished()) {
+            if let Some(stats) = crate::stats::stats_snapshot().get(&name) {
+                assert_eq!(stats.hit_rate, window::rate(stats.hits, stats.misses));
+                assert!(stats.total_requests() >= last_total);
+                last_total = stats.total_requests();
+            }
+        }
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        let stats = crate::stats::get_stats(&name).unwrap();
+        assert_eq!(stats.total_requests(), 8_000);
+    }
+
+    #[test]
+    fn test_peek_leaves_lru_order_alone() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::Lru(2));
+        cache.insert(1, 10);
+        std::thread::sleep(Duration::from_millis(2));
+        cache.insert(2, 20);
+        assert_eq!(cache.peek(&1), Some(10));
+        assert_eq!(cache.hit_count(), 0);
+        cache.insert(3, 30);
+        // Entry 1 was peeked, not touched, so it is still the eviction candidate
+        assert_eq!(cache.peek(&1), None);
+        assert_eq!(cache.peek(&2), Some(20));
+    }
+
+    #[test]
+    fn test_namespace_matching_is_exact() {
+        let user: u32 = cache_get("ns_exact_user", "id", || 1);
+        let users: u32 = cache_get("ns_exact_users", "id", || 2);
+        assert_eq!((user, users), (1, 2));
+        assert!(cache_invalidate("ns_exact_user", "id"));
+        cache_clear_namespace("ns_exact_user");
+        assert_eq!(cache_get("ns_exact_users", "id", || 0), 2);
+        cache_clear_namespace("ns_exact");
+        assert_eq!(cache_get("ns_exact_users", "id", || 0), 2);
+    }
+
+    #[test]
+    fn test_namespace_counts_match_exactly() {
+        let _: u32 = cache_get("counts_ns", "a", || 1);
+        let _: u32 = cache_get("counts_ns", "a", || 1);
+        let _: u32 = cache_get("counts_ns", "a", || 1);
+        let _: u32 = cache_get("counts_ns_other", "a", || 1);
+        assert_eq!(namespace_counts("counts_ns"), (2, 1));
+        assert!((namespace_hit_rate("counts_ns") - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(namespace_counts("counts"), (0, 0));
+        assert_eq!(namespace_hit_rate("counts"), 0.0);
+    }
+
+    #[test]
+    fn test_invalidate_prefix() {
+        let cache: Cache<String, u32> = Cache::new();
+        cache.insert("session:1".to_string(), 1);
+        cache.insert("session:2".to_string(), 2);
+        cache.insert("user:1".to_string(), 3);
+        assert_eq!(cache.invalidate_prefix("session:"), 2);
+        assert_eq!(cache.keys(), vec!["user:1".to_string()]);
+
+        let _: u32 = cache_get("prefix_ns", "session:a", || 1);
+        let _: u32 = cache_get_with_limit("prefix_ns", "session:b", 5, || 2);
+        assert_eq!(cache_invalidate_prefix("prefix_ns", "session:"), 2);
+    }
+
+    #[test]
+    fn test_insert_and_invalidate_take_return_previous_value() {
+        let cache: Cache<u32, String> = Cache::new();
+        assert_eq!(cache.insert(1, "old".into()), None);
+        assert_eq!(cache.insert(1, "new".into()), Some("old".into()));
+        assert_eq!(cache.invalidate_take(&1), Some("new".into()));
+        assert_eq!(cache.invalidate_take(&1), None);
+
+        cache.insert_with_ttl(2, "stale".into(), Duration::from_millis(5), TtlType::Fixed);
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(cache.insert(2, "fresh".into()), None);
+    }
+
+    #[test]
+    fn test_live_len_skips_expired_entries() {
+        let cache: Cache<u32, u32> = Cache::new();
+        cache.insert_with_ttl(1, 1, Duration::from_millis(10), TtlType::Fixed);
+        cache.insert(2, 2);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.live_len(), 1);
+        cache.invalidate(&2);
+        assert!(!cache.is_empty());
+        assert!(cache.is_truly_empty());
+    }
+
+    #[test]
+    fn test_watermark_evicts_in_batches() {
+        let cache: Cache<u32, u32> = Cache::with_watermark(EvictionPolicy::Lru(10), 0.8);
+        cache.insert_many((0..10).map(|i| (i, i)));
+        assert_eq!(cache.len(), 10);
+        cache.insert(10, 10);
+        assert_eq!(cache.len(), 8);
+        cache.insert(11, 11);
+        cache.insert(12, 12);
+        assert_eq!(cache.len(), 10);
+    }
+
+    #[test]
+    fn test_set_entry_ttl_extends_live_entry() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::Ttl {
+            duration: Duration::from_millis(30),
+            ttl_type: TtlType::Sliding,
+        });
+        cache.insert(1, 10);
+        assert_eq!(
+            cache.entry_ttl(&1),
+            Some((Duration::from_millis(30), TtlType::Sliding))
+        );
+        assert!(cache.set_entry_ttl(&1, Duration::from_secs(60), TtlType::Fixed));
+        assert!(!cache.set_entry_ttl(&2, Duration::from_secs(60), TtlType::Fixed));
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(cache.get_if_cached(&1), Some(10));
+        assert_eq!(
+            cache.entry_ttl(&1),
+            Some((Duration::from_secs(60), TtlType::Fixed))
+        );
+    }
+
+    #[test]
+    fn test_get_with_timeout_falls_back_to_stale() {
+        let cache: Cache<u32, u32> = Cache::with_expiry(
+            EvictionPolicy::Ttl {
+                duration: Duration::from_millis(10),
+                ttl_type: TtlType::Fixed,
+            },
+            ExpiryStrategy::OnAccessOnly,
+        );
+        let slow = || {
+            std::thread::sleep(Duration::from_millis(300));
+            0
+        };
+        let timeout = Duration::from_millis(20);
+        // Generous for the fast path, which still has to start a thread
+        assert_eq!(
+            cache.get_with_timeout(&1, Duration::from_secs(10), || 10),
+            Ok(10)
+        );
+        assert_eq!(
+            cache.get_with_timeout(&2, timeout, slow),
+            Err(CacheError::Timeout(timeout))
+        );
+        std::thread::sleep(Duration::from_millis(20));
+        // Key 1 expired but is still stored, so it stands in for the slow source
+        assert_eq!(cache.get_with_timeout(&1, timeout, slow), Ok(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "source exploded")]
+    fn test_get_with_timeout_propagates_compute_panic() {
+        let cache: Cache<u32, u32> = Cache::new();
+        let _ = cache.get_with_timeout(&1, Duration::from_secs(10), || panic!("source exploded"));
+    }
+
+    #[test]
+    fn test_serialized_get_with_timeout_computes_once() {
+        use std::sync::atomic::AtomicU64;
+        let cache: Cache<u32, u32> =
+            Cache::with_compute_policy(EvictionPolicy::None, ComputePolicy::Serialized);
+        let computed = Arc::new(AtomicU64::new(0));
+        let callers: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = cache.clone();
+                let computed = Arc::clone(&computed);
+                std::thread::spawn(move || {
+                    cache.get_with_timeout(&1, Duration::from_secs(10), move || {
+                        computed.fetch_add(1, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(20));
+                        7
+                    })
+                })
+            })
+            .collect();
+        for caller in callers {
+            assert_eq!(caller.join().unwrap(), Ok(7));
+        }
+        assert_eq!(computed.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_stale_if_error_serves_expired_value() {
+        let cache: Cache<u32, u32> = Cache::with_stale_if_error(
+            EvictionPolicy::Ttl {
+                duration: Duration::from_millis(10),
+                ttl_type: TtlType::Fixed,
+            },
+            Duration::from_secs(60),
+        );
+        cache.insert(1, 10);
+        std::thread::sleep(Duration::from_millis(20));
+        // Writes must not sweep the retained entry
+        cache.insert(2, 20);
+        assert_eq!(cache.get_if_cached(&1), None);
+        assert_eq!(
+            cache.get_or_try_tracked(&1, || Err::<u32, _>("down")),
+            Ok((10, CacheOutcome::Stale))
+        );
+        assert_eq!(cache.get_or_try(&1, || Err::<u32, _>("down")), Ok(10));
+        assert_eq!(
+            cache.get_or_try_tracked(&1, || Ok::<_, &str>(11)),
+            Ok((11, CacheOutcome::Miss))
+        );
+        assert_eq!(cache.get_or_try(&3, || Err::<u32, _>("down")), Err("down"));
+    }
+
+    #[test]
+    fn test_eviction_does_not_clone_keys() {
+        use std::sync::atomic::AtomicUsize;
+        static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(PartialEq, Eq, Hash)]
+        struct BigKey(String);
+        impl Clone for BigKey {
+            fn clone(&self) -> Self {
+                CLONES.fetch_add(1, Ordering::Relaxed);
+                BigKey(self.0.clone())
+            }
+        }
+
+        let cache: Cache<BigKey, u32> = Cache::with_policy(EvictionPolicy::Lru(4));
+        for i in 0..16 {
+            cache.insert(BigKey(format!("{:0>1024}", i)), i);
+        }
+        assert_eq!(cache.len(), 4);
+        assert_eq!(CLONES.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_lru_order_predicts_eviction() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::Lru(3));
+        cache.insert_many([(1, 1), (2, 2), (3, 3)]);
+        cache.get(&1, || 0);
+        assert_eq!(cache.lru_order(), [2, 3, 1]);
+        cache.insert(4, 4);
+        assert!(!cache.contains_key(&2));
+        assert_eq!(cache.lru_order(), [3, 1, 4]);
+    }
+
+    #[test]
+    fn test_with_shards() {
+        let cache: Cache<u32, u32> = Cache::with_shards(EvictionPolicy::Lru(100), 4);
+        assert_eq!(cache.shard_count(), 4);
+        cache.insert_many((0..50).map(|i| (i, i)));
+        assert_eq!(cache.len(), 50);
+        let sizes = cache.shard_sizes();
+        assert_eq!((sizes.len(), sizes.iter().sum::<usize>()), (4, 50));
+
+        let preallocated: Cache<u32, u32> = Cache::with_capacity(EvictionPolicy::None, 10_000);
+        assert!(preallocated.storage.capacity() >= 10_000);
+        let built: Cache<u32, u32> = Cache::builder().shards(4).initial_capacity(1_000).build();
+        assert!(built.storage.capacity() >= 1_000);
+        assert!(Cache::<u32, u32>::new().shard_count() > 1);
+    }
+
+    #[test]
+    fn test_tiny_lfu_resists_scans() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        // A fixed hasher keeps the sketch's collisions the same on every run
+        let cache: Cache<u32, u32, BuildHasherDefault<DefaultHasher>> = Cache::with_hasher(
+            EvictionPolicy::TinyLfu { limit: 50 },
+            BuildHasherDefault::default(),
+        );
+        for _ in 0..5 {
+            for key in 0..50 {
+                cache.get(&key, || key);
+            }
+        }
+        // A one-off scan must not flush the hot keys
+        for key in 1_000..1_500 {
+            assert_eq!(cache.get(&key, || key), key);
+        }
+        assert_eq!(cache.len(), 50);
+        assert!((0..50).all(|key| cache.contains_key(&key)));
+    }
+
+    #[test]
+    fn test_tiny_lfu_counts_each_access_once() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::TinyLfu { limit: 10 });
+        let estimate = |key: u32| {
+            let hash = cache.storage.hasher().hash_one(key);
+            cache.with_sketch(|sketch| sketch.estimate(hash)).unwrap()
+        };
+        cache.get(&1, || 1); // Miss
+        assert_eq!(estimate(1), 1);
+        cache.get(&1, || 1); // Hit
+        assert_eq!(estimate(1), 2);
+        cache.insert(2, 2);
+        assert_eq!(estimate(2), 1);
+    }
+
+    #[test]
+    fn test_tiny_lfu_always_stores_inserts() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::TinyLfu { limit: 2 });
+        for _ in 0..3 {
+            cache.get(&1, || 1);
+            cache.get(&2, || 2);
+        }
+        // A cold key is stored anyway, displacing the least recently used
+        assert_eq!(cache.insert(3, 3), None);
+        assert_eq!(cache.peek(&3), Some(3));
+        cache.warm([(4, 4)]);
+        assert_eq!(cache.peek(&4), Some(4));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_tiny_lfu_sketch_follows_limit_changes() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::TinyLfu { limit: 10 });
+        cache.get(&1, || 1);
+        assert_eq!(cache.with_sketch(|sketch| sketch.fits(10)), Some(true));
+        cache.set_policy(EvictionPolicy::TinyLfu { limit: 100_000 });
+        cache.get(&1, || 1);
+        assert_eq!(cache.with_sketch(|sketch| sketch.fits(100_000)), Some(true));
+    }
+
+    #[test]
+    fn test_update_in_place() {
+        let cache: Cache<&str, Vec<u32>> = Cache::new();
+        cache.insert("list", vec![1]);
+        assert!(cache.update_in_place(&"list", |list| list.push(2)));
+        assert_eq!(cache.peek(&"list"), Some(vec![1, 2]));
+        assert!(!cache.update_in_place(&"missing", |list| list.push(3)));
+
+        cache.insert_with_ttl("short", vec![], Duration::from_millis(5), TtlType::Fixed);
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(!cache.update_in_place(&"short", |list| list.push(4)));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_disabled_cache_always_computes() {
+        let cache: Cache<u32, u32> = Cache::disabled();
+        assert_eq!(cache.insert(1, 1), None);
+        assert_eq!(cache.get(&1, || 2), 2);
+        assert_eq!(cache.get(&1, || 3), 3);
+        assert_eq!(
+            (cache.len(), cache.hit_count(), cache.miss_count()),
+            (0, 0, 2)
+        );
+
+        let cache: Cache<u32, u32> = Cache::new();
+        cache.insert(1, 1);
+        cache.set_policy(EvictionPolicy::Disabled);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_set_policy_shrinks_to_new_limit() {
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::Lru(10));
+        cache.insert_many((0..10).map(|i| (i, i)));
+        cache.get(&0, || 0);
+        cache.set_policy(EvictionPolicy::Lru(3));
+        assert_eq!(cache.policy(), EvictionPolicy::Lru(3));
+        assert_eq!(cache.len(), 3);
+        assert!(cache.contains_key(&0));
+
+        cache.clone().set_policy(EvictionPolicy::Lru(20));
+        assert_eq!(cache.capacity(), Some(20));
+    }
+
+    #[test]
+    fn test_compute_closures_can_borrow_mutably() {
+        let cache: Cache<u32, u32> = Cache::new();
+        let mut computed = Vec::new();
+        cache.get_many(&[1, 2], |missing| {
+            computed.extend_from_slice(missing);
+            missing.iter().map(|&key| (key, key * 10)).collect()
+        });
+        cache.get(&3, || {
+            computed.push(3);
+            30
+        });
+        assert_eq!(computed, [1, 2, 3]);
+
+        let mut visited = 0;
+        cache.retain(|_, value| {
+            visited += 1;
+            *value > 10
+        });
+        assert_eq!((visited, cache.len()), (3, 2));
+    }
+
+    #[test]
+    fn test_namespace_listing_and_stats() {
+        let _: u32 = cache_get("dash::orders", "a", || 1);
+        let _: u32 = cache_get_with_limit("dash::orders", "b", 10, || 2);
+        let _: u32 = cache_get_with_limit("dash::orders", "b", 10, || 2);
+        let namespaces = cache_namespaces();
+        assert_eq!(
+            namespaces.iter().filter(|ns| *ns == "dash::orders").count(),
+            1
+        );
+        let stats = cache_stats_for_namespace("dash::orders");
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 2));
+        assert_eq!(cache_stats_for_namespace("dash").total_requests(), 0);
+    }
+
+    #[test]
+    fn test_invalidate_key_across_namespaces() {
+        let _: u32 = cache_get("cascade::users", "user-91", || 1);
+        let _: u32 = cache_get("cascade::orders", "user-91", || 2);
+        let _: u32 = cache_get("cascade::orders", "user-92", || 3);
+        assert_eq!(cache_invalidate_key_global("user-91"), 2);
+        assert_eq!(cache_stats_for_namespace("cascade::orders").entries, 1);
+    }
+
+    #[test]
+    fn test_namespace_shares_one_cache_across_policies() {
+        let first: u32 = cache_get("one_policy", "k", || 1);
+        let second: u32 = cache_get_with_limit("one_policy", "k", 5, || 2);
+        assert_eq!((first, second), (1, 1));
+        assert_eq!(
+            cache_namespace_policy("one_policy"),
+            Some(EvictionPolicy::None)
+        );
+
+        cache_configure_namespace("configured", EvictionPolicy::Lru(1));
+        let _: u32 = cache_get("configured", "a", || 1);
+        let _: u32 = cache_get("configured", "b", || 2);
+        assert_eq!(cache_stats_for_namespace("configured").entries, 1);
+
+        let replaced = get_global_cache_storage()
+            .get("configured")
+            .map(|cache| cache.stats_name())
+            .unwrap();
+        assert!(get_global_stats().get(&replaced).is_some());
+        cache_configure_namespace("configured", EvictionPolicy::Lru(2));
+        assert!(get_global_stats().get(&replaced).is_none());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_for_each_visits_live_entries() {
+        use std::sync::atomic::AtomicU64;
+        let cache: Cache<u32, u64> = Cache::new();
+        cache.insert_many((0..1_000).map(|i| (i, i as u64)));
+        cache.insert_with_ttl(5_000, 1_000_000, Duration::ZERO, TtlType::Fixed);
+        let sum = AtomicU64::new(0);
+        cache.par_for_each(|_, value| {
+            sum.fetch_add(*value, Ordering::Relaxed);
+        });
+        assert_eq!(sum.into_inner(), (0..1_000).sum::<u64>());
+    }
+
+    #[test]
+    fn test_expiry_strategies() {
+        let short = Duration::from_millis(10);
+        let lazy: Cache<u32, u32> =
+            Cache::with_expiry(EvictionPolicy::None, ExpiryStrategy::OnAccessOnly);
+        lazy.insert_with_ttl(1, 1, short, TtlType::Fixed);
+        std::thread::sleep(Duration::from_millis(20));
+        lazy.insert(2, 2);
+        assert_eq!(lazy.len(), 2);
+        assert_eq!(lazy.purge_expired(), 1);
+        assert_eq!(lazy.len(), 1);
+
+        let reaped: Cache<u32, u32> = Cache::with_expiry(
+            EvictionPolicy::None,
+            ExpiryStrategy::Reaper(Duration::from_millis(5)),
+        );
+        reaped.insert_with_ttl(1, 1, short, TtlType::Fixed);
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(reaped.is_empty());
+    }
+
+    #[test]
+    fn test_get_under_concurrent_eviction() {
+        use std::sync::atomic::AtomicU64;
+        let cache: Cache<u32, u32> = Cache::with_policy(EvictionPolicy::Lru(4));
+        let computed = Arc::new(AtomicU64::new(0));
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = cache.clone();
+                let computed = Arc::clone(&computed);
+                std::thread::spawn(move || {
+                    for i in 0..2_000u32 {
+                        let key = i % 8;
+                        let value = cache.get(&key, || {
+                            computed.fetch_add(1, Ordering::Relaxed);
+                            key * 2
+                        });
+                        assert_eq!(value, key * 2);
+                    }
+                })
+            })
+            .collect();
+        let writer = {
+            let cache = cache.clone();
+            std::thread::spawn(move || {
+                for i in 0..2_000u32 {
+                    cache.insert(100 + i % 50, 0);
+                }
+            })
+        };
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        writer.join().unwrap();
+        assert_eq!(cache.hit_count() + cache.miss_count(), 8_000);
+        assert_eq!(cache.miss_count(), computed.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_serialized_compute_runs_once() {
+        use std::sync::atomic::AtomicU64;
+        let cache: Cache<u32, u32> =
+            Cache::with_compute_policy(EvictionPolicy::None, ComputePolicy::Serialized);
+        let computed = Arc::new(AtomicU64::new(0));
+        let start = Arc::new(std::sync::Barrier::new(8));
+        let callers: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                let computed = Arc::clone(&computed);
+                let start = Arc::clone(&start);
+                std::thread::spawn(move || {
+                    start.wait();
+                    cache.get(&1, || {
+                        computed.fetch_add(1, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(20));
+                        7
+                    })
+                })
+            })
+            .collect();
+        for caller in callers {
+            assert_eq!(caller.join().unwrap(), 7);
+        }
+        assert_eq!(computed.load(Ordering::SeqCst), 1);
+        assert!(cache.compute_locks.is_empty());
+        assert_eq!(cache.inflight_count(), 0);
+        // Callers that arrived after the value was stored hit without waiting
+        assert!((1..=7).contains(&cache.deduped_count()));
+        assert_eq!(cache.stats().deduped, cache.deduped_count());
+    }
+
+    #[test]
+    fn test_entry_metadata() {
+        let cache: Cache<&str, u32> = Cache::new();
+        let meta = HashMap::from([("version".to_string(), "3".to_string())]);
+        cache.insert_with_meta("config", 1, meta.clone());
+        cache.insert("plain", 2);
+        assert_eq!(cache.get_meta(&"config"), Some(meta));
+        assert_eq!(cache.get_meta(&"plain"), None);
+        cache.insert("config", 4);
+        assert_eq!(cache.get_meta(&"config"), None);
+        // Like `insert`, the tagged and TTL variants hand back what they replace
+        assert_eq!(cache.insert_with_meta("config", 5, HashMap::new()), Some(4));
+        let ttl = Duration::from_secs(60);
+        assert_eq!(
+            cache.insert_with_ttl("config", 6, ttl, TtlType::Fixed),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_dynamic_ttl_from_value() {
+        let cache: Cache<&str, u32> = Cache::new();
+        let short = cache.get_with_dynamic_ttl(&"short", || (1, Some(Duration::from_millis(10))));
+        let forever = cache.get_with_dynamic_ttl(&"forever", || (2, None));
+        assert_eq!((short, forever), (1, 2));
+        assert!(cache.ttl_remaining(&"short").is_some());
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(cache.get_if_cached(&"short"), None);
+        assert_eq!(cache.get_if_cached(&"forever"), Some(2));
+    }
+
+    #[test]
+    fn test_counters_track_clones() {
+        let cache: Cache<u32, u32> = Cache::new();
+        let counters = cache.counters();
+        let clone = cache.clone();
+        clone.get(&1, || 1);
+        clone.get(&1, || 1);
+        drop(clone);
+        assert_eq!((counters.hits(), counters.misses()), (1, 1));
+        assert_eq!(counters.hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn test_clear_and_reset_zeroes_counters() {
+        let cache: Cache<u32, u32> = Cache::new();
+        cache.get(&1, || 1);
+        cache.get(&1, || 1);
+        cache.clear();
+        assert_eq!(cache.hit_count(), 1);
+        cache.get(&1, || 1);
+        cache.clear_and_reset();
+        assert_eq!((cache.hit_count(), cache.miss_count()), (0, 0));
+        assert_eq!(cache.windowed_hit_rate(Duration::from_secs(60)), 0.0);
+        assert!(cache.compute_latency_stats().is_none());
+        let stats = crate::stats::get_stats(&cache.stats_name()).unwrap();
+        assert_eq!(stats.total_requests(), 0);
+    }
+
+    #[test]
+    fn test_get_tracked_reports_outcome() {
+        let cache: Cache<u32, u32> = Cache::with_refresh_ahead(
+            EvictionPolicy::Ttl {
+                duration: Duration::from_millis(50),
+                ttl_type: TtlType::Fixed,
+            },
+            0.5,
+            |_| 2,
+        );
+        assert_eq!(cache.get_tracked(&1, || 1), (1, CacheOutcome::Miss));
+        assert_eq!(cache.get_tracked(&1, || 1), (1, CacheOutcome::Hit));
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get_tracked(&1, || 1), (1, CacheOutcome::Refreshed));
+    }
+
+    #[test]
+    fn test_key_validator_bypasses_cache() {
+        // Only rejects this test's keys so parallel tests are unaffected
+        cache_set_key_validator(|key| !key.starts_with("rejected:"));
+        let first: u32 = cache_get("validated", "rejected:huge", || 1);
+        let second: u32 = cache_get("validated", "rejected:huge", || 2);
+        assert_eq!((first, second), (1, 2));
+        assert_eq!(cache_get("validated", "ok", || 3), 3);
+        assert_eq!(cache_get("validated", "ok", || 4), 3);
+        assert_eq!(cache_stats_for_namespace("validated").entries, 1);
+    }
+
+    #[test]
+    fn test_custom_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        let cache: Cache<u64, u64, BuildHasherDefault<DefaultHasher>> =
+            Cache::with_hasher(EvictionPolicy::Lru(2), BuildHasherDefault::default());
+        assert_eq!(cache.get(&1, || 10), 10);
+        assert_eq!(cache.get(&1, || 0), 10);
+        cache.insert(2, 20);
+        cache.insert(3, 30);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_reaper_sweeps_until_stopped() {
+        let cache: Cache<u32, u32> =
+            Cache::with_expiry(EvictionPolicy::None, ExpiryStrategy::OnAccessOnly);
+        cache.insert_with_ttl(1, 1, Duration::from_millis(5), TtlType::Fixed);
+        let (task, stop) = cache.spawn_async_reaper(Duration::from_millis(10));
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(cache.len(), 0);
+
+        stop.stop();
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("reaper stops")
+            .unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_reaper_ends_when_cache_dropped_alongside_thread_reaper() {
+        let cache: Cache<u32, u32> = Cache::builder()
+            .expiry(ExpiryStrategy::Reaper(Duration::from_millis(10)))
+            .build();
+        let (task, _stop) = cache.spawn_async_reaper(Duration::from_millis(10));
+        drop(cache);
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("reaper ends")
+            .unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_get_async_single_flight() {
+        use std::sync::atomic::AtomicUsize;
+
+        let cache: Cache<String, u64> = Cache::new();
+        let computations = Arc::new(AtomicUsize::new(0));
+        let mut handles = Vec::new();
+        for _ in 0..8 {
+            let cache = cache.clone();
+            let computations = Arc::clone(&computations);
+            handles.push(tokio::spawn(async move {
+                cache
+                    .get_async(&"key".to_string(), || async move {
+                        computations.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        42
+                    })
+                    .await
+            }));
+        }
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), 42);
+        }
+        assert_eq!(computations.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.miss_count(), 1);
+        assert_eq!(cache.hit_count(), 7);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    #[should_panic(expected = "cyclic cache computation")]
+    async fn test_get_async_detects_cyclic_compute() {
+        let cache: Cache<u32, u32> = Cache::new();
+        cache
+            .get_async(&1, || async {
+                // Another key nests fine; asking for our own key would wait on our own lock
+                let two = cache.get_async(&2, || async { 2 }).await;
+                two + cache.get_async(&1, || async { 0 }).await
+            })
+            .await;
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_get_async_cancelled_compute_clears_inflight() {
+        let cache: Cache<u32, u32> = Cache::new();
+        let result = cache
+            .get_with_timeout_async(&1, Duration::from_millis(10), || async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                1
+            })
+            .await;
+        assert!(matches!(result, Err(CacheError::Timeout(_))));
+        assert_eq!(cache.inflight_count(), 0);
+        assert_eq!(cache.get_async(&1, || async { 2 }).await, 2);
+    }
+
+    /// Collects the `(namespace, key, outcome)` of each `fondue` event
+    #[cfg(feature = "tracing")]
+    #[derive(Default)]
+    struct EventLog(Mutex<Vec<[String; 3]>>);
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for EventLog {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            struct Fields([String; 3]);
+            impl tracing::field::Visit for Fields {
+                fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                    self.record_debug(field, &format_args!("{}", value));
+                }
+
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    let slot = match field.name() {
+                        "namespace" => 0,
+                        "key" => 1,
+                        "outcome" => 2,
+                        _ => return,
+                    };
+                    self.0[slot] = format!("{:?}", value);
+                }
+            }
+            if event.metadata().target() == "fondue" {
+                let mut fields = Fields(Default::default());
+                event.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0);
+            }
         }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_reports_each_outcome() {
+        let log = Arc::new(EventLog::default());
+        let clock = crate::clock::ManualClock::new();
+        let cache: Cache<u32, u32> = Cache::builder()
+            .policy(EvictionPolicy::Lru(1))
+            .name("traced")
+            .trace_keys(|key: &u32| key.to_string())
+            .clock(clock.clone())
+            .build();
+        tracing::subscriber::with_default(Arc::clone(&log), || {
+            cache.get(&1, || 1);
+            cache.get(&1, || 1);
+            clock.advance(Duration::from_secs(1));
+            cache.get(&2, || 2);
+            clock.advance(Duration::from_secs(1));
+            cache.insert_with_ttl(3, 3, Duration::from_secs(1), TtlType::Fixed);
+            clock.advance(Duration::from_secs(2));
+            cache.get_if_cached(&3);
+        });
+        let events: Vec<String> = log
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|[namespace, key, outcome]| format!("{} {} {}", namespace, key, outcome))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                "traced 1 miss",
+                "traced 1 hit",
+                "traced 2 miss",
+                "traced 1 evicted",
+                "traced 2 evicted",
+                "traced 3 expired",
+            ]
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_redacts_keys_by_default() {
+        let log = Arc::new(EventLog::default());
+        let cache: Cache<u32, u32> = Cache::builder().name("redacted").build();
+        tracing::subscriber::with_default(Arc::clone(&log), || cache.get(&1, || 1));
+        let events = log.0.lock().unwrap();
+        assert_eq!(events[0][1], "<redacted>");
     }
 }
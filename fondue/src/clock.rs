@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Source of the time a cache stamps entries with and checks TTLs against
+///
+/// Set one with `CacheBuilder::clock`; caches default to `SystemClock`.
+/// Hit-rate windows and compute latencies always use real time.
+pub trait Clock: Send + Sync {
+    /// Returns the current instant
+    fn now(&self) -> Instant;
+}
+
+/// The default clock, reading `Instant::now()`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when advanced, for testing expiry without sleeping
+///
+/// Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    start: Instant,
+    offset_nanos: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// Creates a clock stopped at the current instant
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            offset_nanos: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Moves the clock forward by `by`
+    pub fn advance(&self, by: Duration) {
+        let nanos = u64::try_from(by.as_nanos()).unwrap_or(u64::MAX);
+        let _ = self
+            .offset_nanos
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |offset| {
+                Some(offset.saturating_add(nanos))
+            });
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + Duration::from_nanos(self.offset_nanos.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_moves_only_when_advanced() {
+        let clock = ManualClock::new();
+        let shared = clock.clone();
+        let before = clock.now();
+        assert_eq!(clock.now(), before);
+        shared.advance(Duration::from_secs(5));
+        assert_eq!(clock.now() - before, Duration::from_secs(5));
+    }
+}
@@ -1,135 +1,384 @@
-use crate::cache::{Cache, EvictionPolicy, TtlType};
-use crate::stats::CacheStats;
+use crate::cache::{
+    get_parsed, Cache, EvictionPolicy, ParseErrorPolicy, TtlType, RECENT_HIT_WINDOW,
+};
+use crate::stats::{batch_stats, CacheStats};
+use crate::window;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
 
+/// Converts values passed to a `CacheContext` into its stored type `Self`
+///
+/// Every `Clone` type stores itself as it is, so a typed context such as
+/// `CacheContext<u64, Vec<u8>>` takes and returns `V` directly. A `String`
+/// context (the default) also stores any `TextValue` as text.
+pub trait EncodeValue<T>: Clone {
+    /// Converts a value into its stored form
+    fn encode(value: T) -> Self;
+}
+
+/// Converts a `CacheContext`'s stored values back into `T`
+///
+/// A `String` context parses text values with `FromStr`, panicking if that fails.
+pub trait DecodeValue<T>: EncodeValue<T> {
+    /// Converts a stored value back
+    fn decode(self) -> T;
+}
+
+/// Values a `String` context stores as text, formatted with `ToString`
+///
+/// Implemented for the integer and float types, `bool`, `char` and `&str`.
+/// Implement it for your own types to cache them in a `String` context; those
+/// that also implement `FromStr` can be read back.
+pub trait TextValue: ToString {}
+
+impl<V: Clone> EncodeValue<V> for V {
+    fn encode(value: V) -> Self {
+        value
+    }
+}
+
+impl<V: Clone> DecodeValue<V> for V {
+    fn decode(self) -> V {
+        self
+    }
+}
+
+impl<T: TextValue> EncodeValue<T> for String {
+    fn encode(value: T) -> Self {
+        value.to_string()
+    }
+}
+
+impl<T> DecodeValue<T> for String
+where
+    T: TextValue + std::str::FromStr,
+    T::Err: std::fmt::Debug,
+{
+    fn decode(self) -> T {
+        self.parse::<T>().expect("Failed to parse cached value")
+    }
+}
+
+macro_rules! impl_text_values {
+    ($($ty:ty),*) => {
+        $(impl TextValue for $ty {})*
+    };
+}
+
+impl_text_values!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char
+);
+
+impl TextValue for &str {}
+
 /// A named cache context that groups related cache operations
-pub struct CacheContext {
+///
+/// Entries are stored in one sub-cache per eviction policy, so a limit passed to
+/// `get_with_limit` bounds every entry cached through that limit. A key is looked
+/// up across all sub-caches, so it is served from wherever it was first stored.
+///
+/// `CacheContext::new` stores `String` keys and values, converting `TextValue`s
+/// with `ToString` and `FromStr`; `CacheContext::<K, V>::new_typed` stores keys
+/// and values of any other types as they are.
+pub struct TypedCacheContext<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
     name: String,
-    caches: Arc<Mutex<HashMap<String, Cache<String, String>>>>,
+    caches: Arc<SubCaches<K, V>>,
+}
+
+/// A context with `String` keys and values unless others are given
+pub type CacheContext<K = String, V = String> = TypedCacheContext<K, V>;
+
+/// The sub-caches shared by a context and its clones, one per eviction policy
+///
+/// Dropped with the last clone, which removes the sub-caches' stats rows.
+struct SubCaches<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    by_policy: Mutex<HashMap<EvictionPolicy, Cache<K, V>>>,
 }
 
 impl CacheContext {
     /// Creates a new cache context with the specified name
     pub fn new(name: impl Into<String>) -> Self {
+        Self::new_typed(name)
+    }
+}
+
+impl<K> TypedCacheContext<K, String>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Like `get`, but handles an unparseable cached value according to `on_error`
+    pub fn get_or_recover<F, T>(
+        &self,
+        key: impl Into<K>,
+        on_error: ParseErrorPolicy,
+        compute: F,
+    ) -> T
+    where
+        F: FnOnce() -> T,
+        T: Clone + ToString + std::str::FromStr + Default,
+        T::Err: std::fmt::Debug,
+    {
+        let key = key.into();
+        let cache = self
+            .find(&key)
+            .unwrap_or_else(|| self.cache_for(EvictionPolicy::None));
+        get_parsed(&cache, &key, on_error, compute)
+    }
+}
+
+impl<K, V> TypedCacheContext<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Creates a new cache context storing `K` keys and `V` values
+    pub fn new_typed(name: impl Into<String>) -> Self {
         Self {
             name: name.into(),
-            caches: Arc::new(Mutex::new(HashMap::new())),
+            caches: Arc::new(SubCaches {
+                by_policy: Mutex::new(HashMap::new()),
+            }),
         }
     }
 
     /// Retrieves a cached value or computes and caches it if missing
     ///
-    /// Parses the cached string result into type V.
-    pub fn get<F, V>(&self, key: impl Into<String>, compute: F) -> V
+    /// Values are converted to and from `V` through `EncodeValue` and `DecodeValue`.
+    pub fn get<F, T>(&self, key: impl Into<K>, compute: F) -> T
     where
-        F: FnOnce() -> V,
-        V: Clone + ToString + std::str::FromStr,
-        V::Err: std::fmt::Debug,
+        F: FnOnce() -> T,
+        V: DecodeValue<T>,
     {
-        let key = key.into();
-        let cache_key = format!("{}::{}", self.name, key);
-        let cache = {
-            let mut caches = self.caches.lock().unwrap();
-            caches.entry(key.clone()).or_default().clone()
-        };
-        let result = cache.get(&cache_key, || compute().to_string());
-        result.parse::<V>().expect("Failed to parse cached value")
+        self.get_with_policy(key.into(), EvictionPolicy::None, compute)
     }
 
     /// Retrieves a cached value with TTL (defaults to Fixed TTL), or computes and caches it
-    pub fn get_with_ttl<F, V>(&self, key: impl Into<String>, ttl: Duration, compute: F) -> V
+    pub fn get_with_ttl<F, T>(&self, key: impl Into<K>, ttl: Duration, compute: F) -> T
     where
-        F: FnOnce() -> V,
-        V: Clone + ToString + std::str::FromStr,
-        V::Err: std::fmt::Debug,
+        F: FnOnce() -> T,
+        V: DecodeValue<T>,
     {
         self.get_with_ttl_type(key, ttl, TtlType::Fixed, compute)
     }
 
     /// Retrieves a cached value with TTL and specified TTL type, or computes and caches it
-    pub fn get_with_ttl_type<F, V>(
+    pub fn get_with_ttl_type<F, T>(
         &self,
-        key: impl Into<String>,
+        key: impl Into<K>,
         ttl: Duration,
         ttl_type: TtlType,
         compute: F,
-    ) -> V
+    ) -> T
     where
-        F: FnOnce() -> V,
-        V: Clone + ToString + std::str::FromStr,
-        V::Err: std::fmt::Debug,
+        F: FnOnce() -> T,
+        V: DecodeValue<T>,
     {
-        let key = key.into();
-        let cache_key = format!("{}::{}", self.name, key);
-        let cache = {
-            let mut caches = self.caches.lock().unwrap();
-            caches
-                .entry(key.clone())
-                .or_insert_with(|| {
-                    Cache::with_policy(EvictionPolicy::Ttl {
-                        duration: ttl,
-                        ttl_type,
-                    })
-                })
-                .clone()
+        let policy = EvictionPolicy::Ttl {
+            duration: ttl,
+            ttl_type,
         };
-        let result = cache.get(&cache_key, || compute().to_string());
-        result.parse::<V>().expect("Failed to parse cached value")
+        self.get_with_policy(key.into(), policy, compute)
+    }
+
+    /// Retrieves a cached value from an LRU sub-cache bounded to `limit` entries
+    pub fn get_with_limit<F, T>(&self, key: impl Into<K>, limit: usize, compute: F) -> T
+    where
+        F: FnOnce() -> T,
+        V: DecodeValue<T>,
+    {
+        self.get_with_policy(key.into(), EvictionPolicy::Lru(limit), compute)
+    }
+
+    /// Retrieves a cached value from a sub-cache combining TTL expiry and an LRU limit
+    pub fn get_with_ttl_and_limit<F, T>(
+        &self,
+        key: impl Into<K>,
+        ttl: Duration,
+        limit: usize,
+        ttl_type: TtlType,
+        compute: F,
+    ) -> T
+    where
+        F: FnOnce() -> T,
+        V: DecodeValue<T>,
+    {
+        let policy = EvictionPolicy::LruTtl {
+            limit,
+            duration: ttl,
+            ttl_type,
+        };
+        self.get_with_policy(key.into(), policy, compute)
     }
 
     /// Gets a cached value if it exists without computing
-    pub fn get_if_cached<V>(&self, key: impl Into<String>) -> Option<V>
+    ///
+    /// A found entry is touched and counted as a hit; use `peek` to avoid that.
+    pub fn get_if_cached<T>(&self, key: impl Into<K>) -> Option<T>
     where
-        V: Clone + std::str::FromStr,
-        V::Err: std::fmt::Debug,
+        V: DecodeValue<T>,
     {
         let key = key.into();
-        let cache_key = format!("{}::{}", self.name, key);
-        let caches = self.caches.lock().unwrap();
-        let cache = caches.get(&key)?;
-        let cached_value = cache.get_if_cached(&cache_key)?;
-        Some(
-            cached_value
-                .parse::<V>()
-                .expect("Failed to parse cached value"),
-        )
+        let cache = self.find(&key)?;
+        cache.get_if_cached(&key).map(V::decode)
     }
 
-    /// Inserts a value manually into the cache
-    pub fn insert<V>(&self, key: impl Into<String>, value: V)
+    /// Gets a cached value without touching it or counting a hit
+    pub fn peek<T>(&self, key: impl Into<K>) -> Option<T>
     where
-        V: ToString,
+        V: DecodeValue<T>,
     {
         let key = key.into();
-        let cache_key = format!("{}::{}", self.name, key);
-        let cache = {
-            let mut caches = self.caches.lock().unwrap();
-            caches.entry(key.clone()).or_default().clone()
-        };
-        cache.insert(cache_key, value.to_string());
+        self.find(&key)?.peek(&key).map(V::decode)
+    }
+
+    /// Inserts a value manually into the cache, returning the live value it replaced
+    ///
+    /// The replaced value is decoded like the inserted one, so a `String`
+    /// context takes string slices as `String`s here.
+    pub fn insert<T>(&self, key: impl Into<K>, value: T) -> Option<T>
+    where
+        V: DecodeValue<T>,
+    {
+        let key = key.into();
+        let cache = self
+            .find(&key)
+            .unwrap_or_else(|| self.cache_for(EvictionPolicy::None));
+        cache.insert(key, V::encode(value)).map(V::decode)
+    }
+
+    /// Preloads entries in bulk into the default (no eviction) sub-cache
+    pub fn warm<Q, T, I>(&self, entries: I)
+    where
+        Q: Into<K>,
+        V: EncodeValue<T>,
+        I: IntoIterator<Item = (Q, T)>,
+    {
+        let entries: Vec<(K, V)> = entries
+            .into_iter()
+            .map(|(key, value)| (key.into(), V::encode(value)))
+            .collect();
+        self.cache_for(EvictionPolicy::None).warm(entries);
+    }
+
+    /// Inserts a value manually with its own TTL so it expires like `get_with_ttl_type` entries
+    ///
+    /// A key not cached yet goes into the matching TTL sub-cache, which later
+    /// `get_with_ttl` calls for the same key read from. Returns the live value
+    /// it replaced, decoded as in `insert`.
+    pub fn insert_with_ttl<T>(
+        &self,
+        key: impl Into<K>,
+        value: T,
+        ttl: Duration,
+        ttl_type: TtlType,
+    ) -> Option<T>
+    where
+        V: DecodeValue<T>,
+    {
+        let key = key.into();
+        let cache = self.find(&key).unwrap_or_else(|| {
+            self.cache_for(EvictionPolicy::Ttl {
+                duration: ttl,
+                ttl_type: ttl_type.clone(),
+            })
+        });
+        cache
+            .insert_with_ttl(key, V::encode(value), ttl, ttl_type)
+            .map(V::decode)
+    }
+
+    /// Returns the keys of all live entries in this context, sorted
+    pub fn keys(&self) -> Vec<K>
+    where
+        K: Ord,
+    {
+        let mut keys: Vec<K> = self
+            .sub_caches()
+            .iter()
+            .flat_map(|cache| cache.keys())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    /// Returns true if a live entry exists for the key, without touching it
+    pub fn contains(&self, key: impl Into<K>) -> bool {
+        self.find(&key.into()).is_some()
     }
 
     /// Invalidates a specific cached key in this context, returning if it was removed
-    pub fn invalidate(&self, key: impl Into<String>) -> bool {
+    pub fn invalidate(&self, key: impl Into<K>) -> bool {
         let key = key.into();
-        let cache_key = format!("{}::{}", self.name, key);
-        let caches = self.caches.lock().unwrap();
-        if let Some(cache) = caches.get(&key) {
-            cache.invalidate(&cache_key)
-        } else {
-            false
+        let mut removed = false;
+        for cache in self.sub_caches() {
+            removed |= cache.invalidate(&key);
         }
+        removed
+    }
+
+    /// Invalidates a key, returning its value if a live entry was removed
+    pub fn invalidate_take<T>(&self, key: impl Into<K>) -> Option<T>
+    where
+        V: DecodeValue<T>,
+    {
+        let key = key.into();
+        let mut taken = None;
+        for cache in self.sub_caches() {
+            taken = taken.or(cache.invalidate_take(&key));
+        }
+        taken.map(V::decode)
+    }
+
+    /// Removes every key starting with `prefix`, returning how many were removed
+    pub fn invalidate_prefix(&self, prefix: &str) -> usize
+    where
+        K: AsRef<str>,
+    {
+        batch_stats(|| {
+            self.sub_caches()
+                .iter()
+                .map(|cache| cache.invalidate_prefix(prefix))
+                .sum()
+        })
     }
 
-    /// Clears all caches in this context
+    /// Keeps only the entries for which `f(key, value)` returns true
+    ///
+    /// The predicate receives the key and the stored value.
+    pub fn retain<F>(&self, mut f: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        batch_stats(|| {
+            for cache in self.sub_caches() {
+                cache.retain(&mut f);
+            }
+        });
+    }
+
+    /// Clears all caches in this context, removing their stats rows
     pub fn clear(&self) {
-        let mut caches = self.caches.lock().unwrap();
-        for cache in caches.values() {
-            cache.clear();
+        let mut caches = self.caches.by_policy.lock().unwrap();
+        batch_stats(|| {
+            for cache in caches.values() {
+                cache.clear();
+            }
+        });
+        // After the batch, which would otherwise publish the rows again
+        for (_, cache) in caches.drain() {
+            cache.forget_stats();
         }
-        caches.clear();
     }
 
     /// Returns the name of this context
@@ -138,43 +387,86 @@ impl CacheContext {
     }
 
     /// Returns aggregated statistics for this context across all its caches
+    ///
+    /// Cheap enough to poll: it only reads each sub-cache's counters (there is
+    /// one per eviction policy used, not per key) and never walks entries, so
+    /// like a published `Cache`'s stats `entries` includes expired entries not
+    /// yet removed. Use `total_entries` for an exact live count.
     pub fn stats(&self) -> CacheStats {
-        let caches = self.caches.lock().unwrap();
-        let mut total_hits = 0;
-        let mut total_misses = 0;
-        let mut total_entries = 0;
-        for cache in caches.values() {
-            total_hits += cache.hit_count();
-            total_misses += cache.miss_count();
-            total_entries += cache.len();
+        let (mut hits, mut misses, mut entries, mut bytes, mut deduped) = (0, 0, 0, 0, 0);
+        let (mut recent_hits, mut recent_misses) = (0, 0);
+        for cache in self.sub_caches() {
+            let (window_hits, window_misses) = cache.windowed_counts(RECENT_HIT_WINDOW);
+            recent_hits += window_hits;
+            recent_misses += window_misses;
+            hits += cache.hit_count();
+            misses += cache.miss_count();
+            entries += cache.len();
+            bytes += cache.approx_memory_bytes();
+            deduped += cache.deduped_count();
         }
         CacheStats {
             name: self.name.clone(),
-            hits: total_hits,
-            misses: total_misses,
-            entries: total_entries as u64,
-            hit_rate: if total_hits + total_misses > 0 {
-                total_hits as f64 / (total_hits + total_misses) as f64
-            } else {
-                0.0
-            },
+            hits,
+            misses,
+            entries: entries as u64,
+            hit_rate: window::rate(hits, misses),
+            recent_hit_rate: Some(window::rate(recent_hits, recent_misses)),
+            bytes,
+            deduped,
         }
     }
 
-    /// Returns the number of sub-caches in this context
+    /// Returns the number of sub-caches (one per eviction policy used) in this context
     pub fn cache_count(&self) -> usize {
-        let caches = self.caches.lock().unwrap();
+        let caches = self.caches.by_policy.lock().unwrap();
         caches.len()
     }
 
-    /// Returns total count of all cached entries across sub-caches
+    /// Returns total count of live (unexpired) entries across sub-caches
     pub fn total_entries(&self) -> usize {
-        let caches = self.caches.lock().unwrap();
-        caches.values().map(|cache| cache.len()).sum()
+        let caches = self.caches.by_policy.lock().unwrap();
+        caches.values().map(|cache| cache.live_len()).sum()
+    }
+
+    /// Shared body of the `get*` methods
+    fn get_with_policy<F, T>(&self, key: K, policy: EvictionPolicy, compute: F) -> T
+    where
+        F: FnOnce() -> T,
+        V: DecodeValue<T>,
+    {
+        let cache = self.find(&key).unwrap_or_else(|| self.cache_for(policy));
+        cache.get(&key, || V::encode(compute())).decode()
+    }
+
+    /// Returns the sub-cache for `policy`, creating it on first use
+    fn cache_for(&self, policy: EvictionPolicy) -> Cache<K, V> {
+        let mut caches = self.caches.by_policy.lock().unwrap();
+        caches
+            .entry(policy.clone())
+            .or_insert_with(|| Cache::with_policy(policy))
+            .clone()
+    }
+
+    /// Returns the sub-cache currently holding a live entry for `key`
+    fn find(&self, key: &K) -> Option<Cache<K, V>> {
+        self.sub_caches()
+            .into_iter()
+            .find(|cache| cache.contains_key(key))
+    }
+
+    /// Snapshots the sub-caches so callers don't hold the lock while using them
+    fn sub_caches(&self) -> Vec<Cache<K, V>> {
+        let caches = self.caches.by_policy.lock().unwrap();
+        caches.values().cloned().collect()
     }
 }
 
-impl Clone for CacheContext {
+impl<K, V> Clone for TypedCacheContext<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
     fn clone(&self) -> Self {
         Self {
             name: self.name.clone(),
@@ -182,3 +474,153 @@ impl Clone for CacheContext {
         }
     }
 }
+
+impl<K, V> Drop for SubCaches<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn drop(&mut self) {
+        let caches = self
+            .by_policy
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        for cache in caches.values() {
+            cache.forget_stats();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_with_ttl_expires() {
+        let ctx = CacheContext::new("insert_with_ttl");
+        let token = "abc".to_string();
+        assert_eq!(
+            ctx.insert_with_ttl("token", token, Duration::from_millis(20), TtlType::Fixed),
+            None
+        );
+        assert_eq!(
+            ctx.get_if_cached::<String>("token"),
+            Some("abc".to_string())
+        );
+        let value: String = ctx.get_with_ttl("token", Duration::from_millis(20), || "fresh".into());
+        assert_eq!(value, "abc");
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(ctx.get_if_cached::<String>("token"), None);
+    }
+
+    #[test]
+    fn test_keys_and_contains() {
+        let ctx = CacheContext::new("keys");
+        ctx.insert("b", 2);
+        let _: u32 = ctx.get_with_limit("a", 10, || 1);
+        assert_eq!(ctx.keys(), vec!["a".to_string(), "b".to_string()]);
+        assert!(ctx.contains("a"));
+        assert!(!ctx.contains("c"));
+        ctx.invalidate("a");
+        assert_eq!(ctx.keys(), vec!["b".to_string()]);
+    }
+
+    #[test]
+    fn test_get_with_limit_bounds_the_context() {
+        let ctx = CacheContext::new("get_with_limit");
+        for i in 0..5u32 {
+            assert_eq!(ctx.get_with_limit(format!("k{}", i), 3, || i), i);
+        }
+        assert_eq!(ctx.total_entries(), 3);
+        assert_eq!(ctx.cache_count(), 1);
+        // Oldest keys were evicted and are recomputed
+        assert_eq!(ctx.get_with_limit("k0", 3, || 42u32), 42);
+        assert_eq!(ctx.get_with_limit("k4", 3, || 42u32), 4);
+    }
+
+    #[test]
+    fn test_stats_aggregate_across_sub_caches() {
+        let ctx = CacheContext::new("stats_aggregate");
+        let _: u32 = ctx.get("a", || 1);
+        let _: u32 = ctx.get_with_limit("b", 10, || 2);
+        let _: u32 = ctx.get("a", || unreachable!());
+        let _: u32 = ctx.get_with_ttl("c", Duration::from_secs(60), || 3);
+        let stats = ctx.stats();
+        assert_eq!(ctx.cache_count(), 3);
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 3));
+        assert_eq!(stats.recent_hit_rate, Some(0.25));
+    }
+
+    #[test]
+    fn test_typed_context_stores_values_as_is() {
+        let ctx: CacheContext<u64, Arc<Vec<u8>>> = CacheContext::new_typed("typed");
+        let bytes = ctx.get_with_limit(7u64, 10, || Arc::new(vec![1, 2, 3]));
+        assert!(Arc::ptr_eq(&bytes, &ctx.get(7u64, || unreachable!())));
+        ctx.insert(8u64, Arc::new(Vec::new()));
+        assert_eq!(ctx.keys(), [7, 8]);
+        assert_eq!(ctx.invalidate_take(8u64), Some(Arc::new(Vec::new())));
+    }
+
+    #[test]
+    fn test_typed_context_takes_foreign_value_types() {
+        let ctx: CacheContext<u64, Vec<u8>> = CacheContext::new_typed("foreign_values");
+        assert_eq!(ctx.get(1u64, || vec![1, 2]), [1, 2]);
+        assert_eq!(ctx.get_if_cached(1u64), Some(vec![1, 2]));
+
+        let ctx: CacheContext<u64, Option<(u32, String)>> = CacheContext::new_typed("tuples");
+        assert_eq!(ctx.get(1u64, || None), None);
+        ctx.insert(2u64, Some((7, "seven".to_string())));
+        assert_eq!(ctx.peek(2u64), Some(Some((7, "seven".to_string()))));
+
+        // A String context still stores text values as text and can hold strings as is
+        let ctx = CacheContext::new("text_values");
+        ctx.insert("n", 5u32);
+        assert_eq!(ctx.insert("n", 6u32), Some(5));
+        ctx.insert("s", "five".to_string());
+        assert_eq!(ctx.peek::<String>("n").as_deref(), Some("6"));
+        assert_eq!(ctx.peek::<u32>("n"), Some(6));
+        assert_eq!(ctx.peek::<String>("s").as_deref(), Some("five"));
+    }
+
+    #[test]
+    fn test_dropping_last_clone_removes_stats() {
+        let ctx = CacheContext::new("dropped");
+        let _: u32 = ctx.get("a", || 1);
+        let _: u32 = ctx.get_with_limit("b", 10, || 2);
+        let names: Vec<String> = ctx.sub_caches().iter().map(Cache::stats_name).collect();
+        let registered = || {
+            names
+                .iter()
+                .filter(|name| crate::get_stats(name).is_some())
+                .count()
+        };
+        assert_eq!(registered(), 2);
+
+        let clone = ctx.clone();
+        drop(ctx);
+        assert_eq!(registered(), 2);
+        drop(clone);
+        assert_eq!(registered(), 0);
+    }
+
+    #[test]
+    fn test_clear_removes_stats() {
+        let ctx = CacheContext::new("cleared");
+        let _: u32 = ctx.get("a", || 1);
+        let names: Vec<String> = ctx.sub_caches().iter().map(Cache::stats_name).collect();
+        assert!(crate::get_stats(&names[0]).is_some());
+        ctx.clear();
+        assert!(crate::get_stats(&names[0]).is_none());
+        assert_eq!(ctx.cache_count(), 0);
+    }
+
+    #[test]
+    fn test_get_or_recover_recomputes_bad_entries() {
+        let ctx = CacheContext::new("recover");
+        ctx.insert("n", "not a number".to_string());
+        let value: u32 = ctx.get_or_recover("n", ParseErrorPolicy::Recompute, || 5);
+        assert_eq!(value, 5);
+        assert_eq!(ctx.get("n", || 0u32), 5);
+    }
+}
@@ -1,4 +1,7 @@
 use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
 use std::time::Duration;
 use thiserror::Error;
 
@@ -11,16 +14,32 @@ pub enum DurationParseError {
     #[error("no time unit found in duration string")]
     MissingUnit,
 
+    #[error("no number found before the time unit")]
+    MissingNumber,
+
     #[error("invalid number '{0}'")]
     InvalidNumber(String),
 
     #[error("unknown time unit '{0}'")]
     UnknownUnit(String),
+
+    #[error("duration is too large to represent")]
+    Overflow,
+
+    #[error("'{0}' is more precise than a nanosecond")]
+    PrecisionLoss(String),
+
+    #[error("item {index} '{item}' of the list: {source}")]
+    InList {
+        index: usize, // Zero-based position in the comma-separated list
+        item: String,
+        source: Box<DurationParseError>,
+    },
 }
 
 /// Supported time units for duration parsing
-#[derive(Debug, PartialEq, Eq)]
-enum TimeUnit {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeUnit {
     Nanosecond,
     Microsecond,
     Millisecond,
@@ -30,69 +49,349 @@ enum TimeUnit {
     Day,
 }
 
+impl TimeUnit {
+    /// Every unit, largest first
+    pub const ALL: [TimeUnit; 7] = [
+        TimeUnit::Day,
+        TimeUnit::Hour,
+        TimeUnit::Minute,
+        TimeUnit::Second,
+        TimeUnit::Millisecond,
+        TimeUnit::Microsecond,
+        TimeUnit::Nanosecond,
+    ];
+
+    /// Returns the length of one unit in nanoseconds
+    pub fn as_nanos(self) -> u128 {
+        match self {
+            TimeUnit::Nanosecond => 1,
+            TimeUnit::Microsecond => 1_000,
+            TimeUnit::Millisecond => 1_000_000,
+            TimeUnit::Second => 1_000_000_000,
+            TimeUnit::Minute => 60_000_000_000,
+            TimeUnit::Hour => 3_600_000_000_000,
+            TimeUnit::Day => 86_400_000_000_000,
+        }
+    }
+
+    /// Returns the short symbol used by `format_duration`, e.g. "ms"
+    pub fn symbol(self) -> &'static str {
+        match self {
+            TimeUnit::Nanosecond => "ns",
+            TimeUnit::Microsecond => "us",
+            TimeUnit::Millisecond => "ms",
+            TimeUnit::Second => "s",
+            TimeUnit::Minute => "m",
+            TimeUnit::Hour => "h",
+            TimeUnit::Day => "d",
+        }
+    }
+}
+
+impl fmt::Display for TimeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// Every unit string the parser accepts (matched case-insensitively)
+const UNIT_ALIASES: &[(&str, TimeUnit)] = &[
+    ("ns", TimeUnit::Nanosecond),
+    ("nanosecond", TimeUnit::Nanosecond),
+    ("nanoseconds", TimeUnit::Nanosecond),
+    ("us", TimeUnit::Microsecond),
+    ("µs", TimeUnit::Microsecond),
+    ("microsecond", TimeUnit::Microsecond),
+    ("microseconds", TimeUnit::Microsecond),
+    ("ms", TimeUnit::Millisecond),
+    ("millisecond", TimeUnit::Millisecond),
+    ("milliseconds", TimeUnit::Millisecond),
+    ("s", TimeUnit::Second),
+    ("sec", TimeUnit::Second),
+    ("second", TimeUnit::Second),
+    ("seconds", TimeUnit::Second),
+    ("m", TimeUnit::Minute),
+    ("min", TimeUnit::Minute),
+    ("minute", TimeUnit::Minute),
+    ("minutes", TimeUnit::Minute),
+    ("h", TimeUnit::Hour),
+    ("hr", TimeUnit::Hour),
+    ("hour", TimeUnit::Hour),
+    ("hours", TimeUnit::Hour),
+    ("d", TimeUnit::Day),
+    ("day", TimeUnit::Day),
+    ("days", TimeUnit::Day),
+];
+
 impl TryFrom<&str> for TimeUnit {
     type Error = DurationParseError;
 
     /// Converts a string representation of a unit into `TimeUnit`
     fn try_from(unit: &str) -> Result<Self, Self::Error> {
-        match unit.to_lowercase().as_str() {
-            "ns" | "nanosecond" | "nanoseconds" => Ok(TimeUnit::Nanosecond),
-            "us" | "µs" | "microsecond" | "microseconds" => Ok(TimeUnit::Microsecond),
-            "ms" | "millisecond" | "milliseconds" => Ok(TimeUnit::Millisecond),
-            "s" | "sec" | "second" | "seconds" => Ok(TimeUnit::Second),
-            "m" | "min" | "minute" | "minutes" => Ok(TimeUnit::Minute),
-            "h" | "hr" | "hour" | "hours" => Ok(TimeUnit::Hour),
-            "d" | "day" | "days" => Ok(TimeUnit::Day),
-            unknown => Err(DurationParseError::UnknownUnit(unknown.to_string())),
-        }
+        let unit = unit.to_lowercase();
+        UNIT_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == unit)
+            .map(|(_, time_unit)| *time_unit)
+            .ok_or(DurationParseError::UnknownUnit(unit))
     }
 }
 
+/// Lists every unit string `parse_duration` accepts, smallest unit first
+///
+/// Matching is case-insensitive, so `"MS"` is accepted as well as `"ms"`.
+pub fn duration_units() -> &'static [&'static str] {
+    static UNITS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    UNITS.get_or_init(|| UNIT_ALIASES.iter().map(|(alias, _)| *alias).collect())
+}
+
 /// Parses duration strings like "1.5h", "200ms", "30s", supporting fractional values.
 /// Returns a `Duration` or a detailed parsing error.
 ///
+/// A bare zero ("0", "0.0") needs no unit and parses to `Duration::ZERO`.
+/// Fractions finer than a nanosecond are rounded half up in every unit; use
+/// `parse_duration_checked` to reject them instead.
+///
 /// # Errors
-/// Returns variants of `DurationParseError` if input is empty, missing unit,
-/// contains an invalid number, or an unknown unit.
+/// Returns variants of `DurationParseError` if input is empty, missing a unit
+/// or number, contains an invalid number, or an unknown unit.
 pub fn parse_duration(s: &str) -> Result<Duration, DurationParseError> {
+    parse_with_unit(s, None)
+}
+
+/// Parses like `parse_duration`, but reads a bare number as a count of `default`
+///
+/// `parse_duration_with_default("30", TimeUnit::Second)` is 30 seconds, while
+/// an explicit unit such as "500ms" still wins. Invalid numbers and unknown
+/// units are rejected exactly as in `parse_duration`.
+pub fn parse_duration_with_default(
+    s: &str,
+    default: TimeUnit,
+) -> Result<Duration, DurationParseError> {
+    parse_with_unit(s, Some(default))
+}
+
+/// Parses like `parse_duration`, but fails instead of rounding to the nearest nanosecond
+///
+/// `parse_duration("1.5ns")` is 2ns; this returns
+/// `DurationParseError::PrecisionLoss` for it, and for anything else whose
+/// value is not a whole number of nanoseconds, such as "0.0000000001s".
+pub fn parse_duration_checked(s: &str) -> Result<Duration, DurationParseError> {
+    match parse_rounded(s, None)? {
+        (_, true) => Err(DurationParseError::PrecisionLoss(s.trim().to_string())),
+        (duration, false) => Ok(duration),
+    }
+}
+
+/// Parses a comma-separated list of durations such as "1s, 2s, 5s, 10s"
+///
+/// Items are trimmed and parsed with `parse_duration`. One trailing comma is
+/// allowed; any other empty item is an error.
+///
+/// # Errors
+/// `DurationParseError::EmptyString` if the list is empty, otherwise
+/// `DurationParseError::InList` naming the first item that failed and why.
+pub fn parse_durations(s: &str) -> Result<Vec<Duration>, DurationParseError> {
+    let s = s.trim();
+    let list = s.strip_suffix(',').unwrap_or(s);
+    if list.trim().is_empty() {
+        return Err(DurationParseError::EmptyString);
+    }
+    list.split(',')
+        .map(str::trim)
+        .enumerate()
+        .map(|(index, item)| {
+            parse_duration(item).map_err(|err| DurationParseError::InList {
+                index,
+                item: item.to_string(),
+                source: Box::new(err),
+            })
+        })
+        .collect()
+}
+
+/// Shared body of the rounding parsers; `default` applies when `s` has no unit
+fn parse_with_unit(s: &str, default: Option<TimeUnit>) -> Result<Duration, DurationParseError> {
+    parse_rounded(s, default).map(|(duration, _)| duration)
+}
+
+/// Parses `s`, also reporting whether it was rounded to the nearest nanosecond
+fn parse_rounded(
+    s: &str,
+    default: Option<TimeUnit>,
+) -> Result<(Duration, bool), DurationParseError> {
     let s = s.trim();
     if s.is_empty() {
         return Err(DurationParseError::EmptyString);
     }
 
     // Find the first alphabetic character to split number and unit
-    let pos = s
-        .find(|c: char| c.is_alphabetic())
-        .ok_or(DurationParseError::MissingUnit)?;
-
-    let (num_str, unit_str) = s.split_at(pos);
-    let num_str = num_str.trim();
-    let unit_str = unit_str.trim();
+    let (num_str, unit_str) = match s.find(|c: char| c.is_alphabetic()) {
+        Some(pos) => {
+            let (num_str, unit_str) = s.split_at(pos);
+            (num_str.trim(), Some(unit_str.trim()))
+        }
+        None if default.is_some() => (s, None),
+        None => {
+            return match s.parse::<f64>() {
+                Ok(number) if number == 0.0 && !s.starts_with('-') => Ok((Duration::ZERO, false)),
+                _ => Err(DurationParseError::MissingUnit),
+            };
+        }
+    };
+    if num_str.is_empty() {
+        return Err(DurationParseError::MissingNumber);
+    }
 
     // Reject negative numbers; durations can't be negative
     if num_str.starts_with('-') {
         return Err(DurationParseError::InvalidNumber(num_str.to_string()));
     }
 
-    // Parse the number as f64 for fractional support
-    let number: f64 = num_str
-        .parse()
+    // Validate the number; the conversion below works on its decimal digits
+    num_str
+        .parse::<f64>()
         .map_err(|_| DurationParseError::InvalidNumber(num_str.to_string()))?;
 
-    let unit = TimeUnit::try_from(unit_str)?;
-
-    // Convert number and unit into std::time::Duration
-    let duration = match unit {
-        TimeUnit::Nanosecond => Duration::from_nanos(number.round() as u64),
-        TimeUnit::Microsecond => Duration::from_micros(number.round() as u64),
-        TimeUnit::Millisecond => Duration::from_millis(number.round() as u64),
-        TimeUnit::Second => Duration::from_secs_f64(number),
-        TimeUnit::Minute => Duration::from_secs_f64(number * 60.0),
-        TimeUnit::Hour => Duration::from_secs_f64(number * 3600.0),
-        TimeUnit::Day => Duration::from_secs_f64(number * 86400.0),
+    let unit = match (unit_str, default) {
+        (Some(unit_str), _) => TimeUnit::try_from(unit_str)?,
+        (None, Some(default)) => default,
+        (None, None) => return Err(DurationParseError::MissingUnit),
     };
 
-    Ok(duration)
+    decimal_to_duration(num_str, unit)
+}
+
+/// Formats a duration in the largest unit that represents it exactly, e.g. "5m" or "1500ms"
+///
+/// The output always parses back to the same duration with `parse_duration`.
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0s".to_string();
+    }
+    let unit = TimeUnit::ALL
+        .into_iter()
+        .find(|unit| nanos % unit.as_nanos() == 0)
+        .expect("every duration is a whole number of nanoseconds");
+    format!("{}{}", nanos / unit.as_nanos(), unit)
+}
+
+/// Splits a duration into whole units, largest first, skipping zero components
+///
+/// `breakdown(Duration::from_secs(3661))` is `[(1, Hour), (1, Minute), (1, Second)]`,
+/// ready to render as "1h 1m 1s". A zero duration yields an empty list.
+pub fn breakdown(duration: Duration) -> Vec<(u64, TimeUnit)> {
+    let mut remaining = duration.as_nanos();
+    let mut parts = Vec::new();
+    for unit in TimeUnit::ALL {
+        let count = remaining / unit.as_nanos();
+        if count > 0 {
+            parts.push((count as u64, unit));
+            remaining %= unit.as_nanos();
+        }
+    }
+    parts
+}
+
+/// A `Duration` that parses from and displays as a human-readable string like "30s"
+///
+/// Implements `FromStr` and `TryFrom<&str>` via `parse_duration` and `Display`
+/// via `format_duration`, so it can be used directly in config structs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct HumanDuration(pub Duration);
+
+impl HumanDuration {
+    /// Returns the wrapped `Duration`
+    pub fn into_inner(self) -> Duration {
+        self.0
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = DurationParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_duration(s).map(HumanDuration)
+    }
+}
+
+impl TryFrom<&str> for HumanDuration {
+    type Error = DurationParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_duration(self.0))
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        HumanDuration(duration)
+    }
+}
+
+impl From<HumanDuration> for Duration {
+    fn from(duration: HumanDuration) -> Self {
+        duration.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for HumanDuration {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for HumanDuration {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Converts a decimal count of `unit` to a `Duration`, rounding half up to the nearest nanosecond
+///
+/// Works on the decimal digits rather than an `f64`, so every unit rounds the
+/// same way and values like "1.1h" are exact. Also reports whether rounding
+/// dropped part of a nanosecond.
+fn decimal_to_duration(
+    num_str: &str,
+    unit: TimeUnit,
+) -> Result<(Duration, bool), DurationParseError> {
+    let digits = num_str.trim_start_matches('+');
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let whole: u128 = match whole {
+        "" => 0,
+        whole => whole.parse().map_err(|_| DurationParseError::Overflow)?,
+    };
+
+    // 24 digits times the longest unit still fits in a u128; any further
+    // non-zero digits only mark the result as rounded
+    let fraction = fraction.trim_end_matches('0');
+    let (kept, dropped) = fraction.split_at(fraction.len().min(24));
+    let scale = 10u128.pow(kept.len() as u32);
+    let numerator = match kept {
+        "" => 0,
+        kept => kept.parse::<u128>().expect("validated digits") * unit.as_nanos(),
+    };
+    let remainder = numerator % scale;
+    let round_up = u128::from(remainder != 0 && remainder * 2 >= scale);
+
+    let nanos = whole
+        .checked_mul(unit.as_nanos())
+        .and_then(|nanos| nanos.checked_add(numerator / scale + round_up))
+        .ok_or(DurationParseError::Overflow)?;
+    let secs = u64::try_from(nanos / 1_000_000_000).map_err(|_| DurationParseError::Overflow)?;
+    let duration = Duration::new(secs, (nanos % 1_000_000_000) as u32);
+    Ok((duration, remainder != 0 || !dropped.is_empty()))
 }
 
 #[cfg(test)]
@@ -111,11 +410,99 @@ mod tests {
         assert_eq!(parse_duration("100ns").unwrap(), Duration::from_nanos(100));
     }
 
+    #[test]
+    fn test_listed_units_all_parse() {
+        let units = duration_units();
+        assert!(units.contains(&"ms") && units.contains(&"µs"));
+        for unit in units {
+            assert!(parse_duration(&format!("1{}", unit)).is_ok(), "{}", unit);
+        }
+    }
+
+    #[test]
+    fn test_default_unit_for_bare_numbers() {
+        assert_eq!(
+            parse_duration_with_default("30", TimeUnit::Second),
+            Ok(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_duration_with_default(" 1.5 ", TimeUnit::Minute),
+            Ok(Duration::from_secs(90))
+        );
+        assert_eq!(
+            parse_duration_with_default("500ms", TimeUnit::Second),
+            Ok(Duration::from_millis(500))
+        );
+        assert_eq!(
+            parse_duration_with_default("-5", TimeUnit::Second),
+            Err(DurationParseError::InvalidNumber("-5".to_string()))
+        );
+        assert_eq!(
+            parse_duration_with_default("5x", TimeUnit::Second),
+            Err(DurationParseError::UnknownUnit("x".to_string()))
+        );
+        assert_eq!(parse_duration("30"), Err(DurationParseError::MissingUnit));
+    }
+
+    #[test]
+    fn test_breakdown_into_whole_units() {
+        assert_eq!(
+            breakdown(Duration::from_secs(3661)),
+            [
+                (1, TimeUnit::Hour),
+                (1, TimeUnit::Minute),
+                (1, TimeUnit::Second)
+            ]
+        );
+        assert_eq!(
+            breakdown(Duration::from_millis(90_500)),
+            [
+                (1, TimeUnit::Minute),
+                (30, TimeUnit::Second),
+                (500, TimeUnit::Millisecond)
+            ]
+        );
+        assert!(breakdown(Duration::ZERO).is_empty());
+    }
+
     #[test]
     fn test_fractional_values() {
         assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
         assert_eq!(parse_duration("0.5m").unwrap(), Duration::from_secs(30));
         assert_eq!(parse_duration("1.25h").unwrap(), Duration::from_secs(4500));
+        assert_eq!(
+            parse_duration("1.5ms").unwrap(),
+            Duration::from_micros(1500)
+        );
+        assert_eq!(parse_duration("1.1h").unwrap(), Duration::from_secs(3960));
+        assert_eq!(parse_duration("2.5us").unwrap(), Duration::from_nanos(2500));
+        assert_eq!(
+            parse_duration("0.25ms").unwrap(),
+            Duration::from_micros(250)
+        );
+    }
+
+    #[test]
+    fn test_sub_nanosecond_rounding() {
+        // Every unit rounds half up to the nearest nanosecond
+        assert_eq!(parse_duration("1.5ns").unwrap(), Duration::from_nanos(2));
+        assert_eq!(parse_duration("1.4ns").unwrap(), Duration::from_nanos(1));
+        assert_eq!(
+            parse_duration("0.0000000015s").unwrap(),
+            Duration::from_nanos(2)
+        );
+        assert_eq!(
+            parse_duration_checked("1.5ns").unwrap_err(),
+            DurationParseError::PrecisionLoss("1.5ns".to_string())
+        );
+        assert_eq!(
+            parse_duration_checked("0.0000000015s").unwrap_err(),
+            DurationParseError::PrecisionLoss("0.0000000015s".to_string())
+        );
+        assert_eq!(
+            parse_duration_checked("1.000001ms").unwrap(),
+            Duration::from_nanos(1_000_001)
+        );
     }
 
     #[test]
@@ -135,11 +522,11 @@ mod tests {
         );
         assert_eq!(
             parse_duration("ms").unwrap_err(),
-            DurationParseError::MissingUnit
+            DurationParseError::MissingNumber
         );
         assert_eq!(
             parse_duration("abc").unwrap_err(),
-            DurationParseError::MissingUnit
+            DurationParseError::MissingNumber
         );
         assert_eq!(
             parse_duration("100xy").unwrap_err(),
@@ -154,4 +541,99 @@ mod tests {
             DurationParseError::InvalidNumber("-10".to_string())
         );
     }
+
+    #[test]
+    fn test_duration_lists() {
+        assert_eq!(
+            parse_durations(" 1s, 2s,500ms, ").unwrap(),
+            [
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_millis(500)
+            ]
+        );
+        let err = parse_durations("1s,,2s").unwrap_err();
+        assert_eq!(
+            err,
+            DurationParseError::InList {
+                index: 1,
+                item: String::new(),
+                source: Box::new(DurationParseError::EmptyString),
+            }
+        );
+        assert_eq!(
+            parse_durations("1s, 5 parsecs").unwrap_err().to_string(),
+            "item 1 '5 parsecs' of the list: unknown time unit 'parsecs'"
+        );
+        assert_eq!(
+            parse_durations(",").unwrap_err(),
+            DurationParseError::EmptyString
+        );
+    }
+
+    #[test]
+    fn test_bare_zero() {
+        assert_eq!(parse_duration("0").unwrap(), Duration::ZERO);
+        assert_eq!(parse_duration(" 0.0 ").unwrap(), Duration::ZERO);
+        assert_eq!(parse_duration("0s").unwrap(), Duration::ZERO);
+        assert_eq!(
+            parse_duration("-0").unwrap_err(),
+            DurationParseError::MissingUnit
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        // Just under the limit
+        assert!(parse_duration("213503982334601d").unwrap() > Duration::from_secs(u64::MAX / 2));
+        assert_eq!(
+            parse_duration("99999999999999999999d").unwrap_err(),
+            DurationParseError::Overflow
+        );
+        assert_eq!(
+            parse_duration("18446744073709551616s").unwrap_err(),
+            DurationParseError::Overflow
+        );
+        assert_eq!(
+            parse_duration("99999999999999999999999999999ns").unwrap_err(),
+            DurationParseError::Overflow
+        );
+        assert!(parse_duration("18446744073709549568ms").is_ok());
+        // Sub-second counts past u64::MAX are fine while the total fits
+        assert_eq!(
+            parse_duration("20000000000000000000us").unwrap(),
+            Duration::from_secs(20_000_000_000_000)
+        );
+        assert_eq!(
+            parse_duration("99999999999999999999ns").unwrap(),
+            Duration::new(99_999_999_999, 999_999_999)
+        );
+    }
+
+    #[test]
+    fn test_human_duration_round_trips() {
+        let parsed: HumanDuration = "1.5m".parse().unwrap();
+        assert_eq!(parsed.into_inner(), Duration::from_secs(90));
+        assert_eq!(parsed.to_string(), "90s");
+        assert_eq!(HumanDuration::try_from("2h").unwrap().to_string(), "2h");
+        assert_eq!(format_duration(Duration::from_millis(1500)), "1500ms");
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+        for text in ["1d", "36h", "250us", "7ns"] {
+            let duration = parse_duration(text).unwrap();
+            assert_eq!(
+                parse_duration(&format_duration(duration)).unwrap(),
+                duration
+            );
+        }
+        assert!("soon".parse::<HumanDuration>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_human_duration_serde() {
+        let duration: HumanDuration = serde_json::from_str("\"5m\"").unwrap();
+        assert_eq!(duration.into_inner(), Duration::from_secs(300));
+        assert_eq!(serde_json::to_string(&duration).unwrap(), "\"5m\"");
+        assert!(serde_json::from_str::<HumanDuration>("\"5 parsecs\"").is_err());
+    }
 }
@@ -0,0 +1,15 @@
+use crate::duration::DurationParseError;
+use thiserror::Error;
+
+/// Errors surfaced by the fallible (`try_`) cache APIs
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CacheError {
+    #[error("invalid TTL: {0}")]
+    InvalidTtl(#[from] DurationParseError),
+
+    #[error("failed to parse cached value '{value}': {reason}")]
+    InvalidValue { value: String, reason: String },
+
+    #[error("computation timed out after {0:?}")]
+    Timeout(std::time::Duration),
+}
@@ -0,0 +1,78 @@
+use crate::stats::LatencyStats;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Number of most recent compute durations kept for percentile estimates
+const SAMPLES: usize = 1024;
+
+/// Bounded record of recent `compute()` durations
+///
+/// Only misses record a sample, so the lock is taken at most once per call
+/// that already paid for a computation.
+#[derive(Debug)]
+pub(crate) struct LatencyRecorder {
+    samples: Mutex<VecDeque<Duration>>,
+}
+
+impl LatencyRecorder {
+    /// Creates an empty recorder
+    pub(crate) fn new() -> Self {
+        Self {
+            samples: Mutex::new(VecDeque::with_capacity(SAMPLES)),
+        }
+    }
+
+    /// Records one compute duration, dropping the oldest once full
+    pub(crate) fn record(&self, elapsed: Duration) {
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() == SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(elapsed);
+    }
+
+    /// Forgets all recorded durations
+    pub(crate) fn reset(&self) {
+        self.samples.lock().unwrap().clear();
+    }
+
+    /// Summarizes the recorded durations, or `None` if nothing was recorded
+    pub(crate) fn stats(&self) -> Option<LatencyStats> {
+        let mut sorted: Vec<Duration> = self.samples.lock().unwrap().iter().copied().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_unstable();
+        let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+        let total: Duration = sorted.iter().sum();
+        Some(LatencyStats {
+            samples: sorted.len() as u64,
+            mean: total / sorted.len() as u32,
+            p50: percentile(0.50),
+            p90: percentile(0.90),
+            p99: percentile(0.99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_over_recent_samples() {
+        let recorder = LatencyRecorder::new();
+        assert!(recorder.stats().is_none());
+        for ms in 1..=100 {
+            recorder.record(Duration::from_millis(ms));
+        }
+        let stats = recorder.stats().unwrap();
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.p50, Duration::from_millis(51));
+        assert_eq!(stats.p90, Duration::from_millis(90));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+        assert_eq!(stats.max, Duration::from_millis(100));
+    }
+}
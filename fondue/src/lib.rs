@@ -1,25 +1,78 @@
+pub mod arc;
+pub mod autotune;
+pub mod builder;
 pub mod cache;
+pub mod clock;
 pub mod context;
 pub mod duration;
+pub mod error;
+mod latency;
+pub mod namespace;
+pub mod negative;
+pub mod registry;
+pub mod sink;
+mod sketch;
+pub mod source;
 pub mod stats;
+pub mod tiered;
+mod window;
 
 #[macro_use]
 mod macros;
 
 // Re-export cache types, functions, macros at the crate root for easy access and macro resolution
+pub use arc::ArcCache;
+pub use autotune::AutotuneConfig;
+pub use builder::CacheBuilder;
 pub use cache::{
-    cache_clear_all, cache_get, cache_get_with_limit, cache_get_with_ttl,
-    cache_get_with_ttl_and_limit, cache_invalidate, Cache, CacheEntry, EvictionPolicy, TtlType,
+    cache_clear_all, cache_clear_key_validator, cache_configure_namespace, cache_get,
+    cache_get_or_recover, cache_get_with_limit, cache_get_with_ttl, cache_get_with_ttl_and_limit,
+    cache_invalidate, cache_invalidate_key_global, cache_invalidate_prefix, cache_namespaces,
+    cache_set_key_validator, cache_set_max_key_len, cache_stats_for_namespace, cache_warm,
+    caching_enabled, default_ttl, namespace_counts, namespace_hit_rate, set_caching_enabled,
+    set_default_ttl, try_cache_get, try_cache_get_with_limit, try_cache_get_with_ttl,
+    try_cache_get_with_ttl_and_limit, Cache, CacheCounters, CacheEntry, CacheOutcome,
+    ComputePolicy, EvictionPolicy, ExpiryStrategy, Fetched, ParseErrorPolicy, TtlType,
 };
+pub use clock::{Clock, ManualClock, SystemClock};
+
+#[cfg(feature = "test-util")]
+pub use cache::reset_global_state;
+#[cfg(feature = "async")]
+pub use cache::{cache_get_async, ReaperStop};
 
 // Re-export context and duration utilities explicitly
-pub use context::CacheContext;
+pub use context::{CacheContext, DecodeValue, EncodeValue, TextValue, TypedCacheContext};
+
+pub use error::CacheError;
+pub use namespace::{CacheKey, Namespace};
+pub use negative::NegativeCache;
+pub use registry::CacheRegistry;
+pub use sink::{CacheSink, WriteCache, WriteMode};
+pub use source::{CacheSource, ReadThroughCache};
+pub use tiered::TieredCache;
+
+// Duration parsing and formatting helpers
+pub use duration::{
+    breakdown, duration_units, format_duration, parse_duration, parse_duration_checked,
+    parse_duration_with_default, parse_durations, HumanDuration, TimeUnit,
+};
 
-// Only expose parse_duration function from duration module
-pub use duration::parse_duration;
+#[cfg(feature = "memoize")]
+pub use fondue_macros::memoize;
 
 // Re-export statistics utilities explicitly
 pub use stats::{
-    aggregate_stats, clear_stats, export_json, get_stats, print_stats, print_stats_table,
-    register_stats, update_stats, CacheStats, GlobalStats,
+    aggregate_stats, clear_stats, export_csv, export_json, get_stats, print_stats,
+    print_stats_table, print_stats_table_sorted, register_stats, stats_snapshot, update_stats,
+    CacheStats, GlobalStats, LatencyStats, SortKey,
 };
+
+#[cfg(feature = "serde")]
+pub use stats::export_json_value;
+
+// Support items referenced by macro expansions; not part of the public API
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::cache::memoize_key;
+}
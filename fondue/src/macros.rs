@@ -11,20 +11,51 @@ macro_rules! cache {
     };
 }
 
+/// Async cache macro; must be used inside an async context.
+/// Usage: `cache_async!("namespace", "key", || async { compute_value().await })`
+///
+/// Expands to `cache_get_async(...).await`, sharing one computation between concurrent callers.
+#[cfg(feature = "async")]
+#[macro_export]
+macro_rules! cache_async {
+    ($ns:expr, $key:expr, $compute:expr) => {
+        $crate::cache_get_async($ns, $key, $compute).await
+    };
+}
+
 /// Cache macro with TTL support.
 /// Usage: `cache_with_ttl!("namespace", "key", "200ms", TtlType::Fixed, || compute_value())`
 ///
 /// Parses the TTL string using `parse_duration` and calls `cache_get_with_ttl`.
+/// An invalid TTL panics with the string and the parse error; use
+/// `try_cache_with_ttl!` to get a `CacheError::InvalidTtl` instead.
+///
+/// ```
+/// use fondue::{cache_with_ttl, try_cache_with_ttl, CacheError, TtlType};
+///
+/// let answer: u32 = cache_with_ttl!("docs", "answer", "1.5s", TtlType::Fixed, || 42);
+/// assert_eq!(answer, 42);
+///
+/// let invalid: Result<u32, CacheError> =
+///     try_cache_with_ttl!("docs", "answer", "5 parsecs", TtlType::Fixed, || 42);
+/// assert!(matches!(invalid, Err(CacheError::InvalidTtl(_))));
+/// ```
 #[macro_export]
 macro_rules! cache_with_ttl {
     ($ns:expr, $key:expr, $ttl:expr, $ttl_type:expr, $compute:expr) => {
-        $crate::cache_get_with_ttl(
-            $ns,
-            $key,
-            $crate::parse_duration($ttl).expect("Invalid TTL"),
-            $ttl_type,
-            $compute,
-        )
+        $crate::cache_get_with_ttl($ns, $key, $crate::__parse_ttl!($ttl), $ttl_type, $compute)
+    };
+}
+
+/// Parses a macro's TTL string, panicking with the string and the reason if invalid
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __parse_ttl {
+    ($ttl:expr) => {
+        match $ttl {
+            ttl => $crate::parse_duration(ttl)
+                .unwrap_or_else(|err| panic!("Invalid TTL {:?}: {}", ttl, err)),
+        }
     };
 }
 
@@ -42,17 +73,89 @@ macro_rules! cache_with_limit {
 /// Cache macro with both TTL and limit support.
 /// Usage: `cache_with_ttl_and_limit!("namespace", "key", "500ms", 5, TtlType::Sliding, || compute_value())`
 ///
-/// Parses TTL string and calls `cache_get_with_ttl_and_limit`.
+/// Parses TTL string and calls `cache_get_with_ttl_and_limit`, panicking like
+/// `cache_with_ttl!` if it is invalid.
 #[macro_export]
 macro_rules! cache_with_ttl_and_limit {
     ($ns:expr, $key:expr, $ttl:expr, $limit:expr, $ttl_type:expr, $compute:expr) => {
         $crate::cache_get_with_ttl_and_limit(
             $ns,
             $key,
-            $crate::parse_duration($ttl).expect("Invalid TTL"),
+            $crate::__parse_ttl!($ttl),
             $limit,
             $ttl_type,
             $compute,
         )
     };
 }
+
+/// Bulk preload macro.
+/// Usage: `cache_warm!("namespace", { "a" => 1, "b" => 2 })`
+///
+/// Loads all pairs into the namespace's default cache with a single eviction pass.
+#[macro_export]
+macro_rules! cache_warm {
+    ($ns:expr, { $($key:expr => $value:expr),* $(,)? }) => {
+        $crate::cache_warm($ns, vec![$(($key.to_string(), $value)),*])
+    };
+}
+
+/// Fallible cache macro returning `Result<V, CacheError>`.
+/// Usage: `try_cache!("namespace", "key", || compute_value())?`
+#[macro_export]
+macro_rules! try_cache {
+    ($ns:expr, $key:expr, $compute:expr) => {
+        $crate::try_cache_get($ns, $key, $compute)
+    };
+}
+
+/// Fallible cache macro with TTL support.
+/// Usage: `try_cache_with_ttl!("namespace", "key", "200ms", TtlType::Fixed, || compute_value())?`
+///
+/// An invalid TTL string yields `CacheError::InvalidTtl` instead of panicking.
+#[macro_export]
+macro_rules! try_cache_with_ttl {
+    ($ns:expr, $key:expr, $ttl:expr, $ttl_type:expr, $compute:expr) => {
+        $crate::parse_duration($ttl)
+            .map_err($crate::CacheError::from)
+            .and_then(|ttl| $crate::try_cache_get_with_ttl($ns, $key, ttl, $ttl_type, $compute))
+    };
+}
+
+/// Fallible cache macro with limit support.
+/// Usage: `try_cache_with_limit!("namespace", "key", 10, || compute_value())?`
+#[macro_export]
+macro_rules! try_cache_with_limit {
+    ($ns:expr, $key:expr, $limit:expr, $compute:expr) => {
+        $crate::try_cache_get_with_limit($ns, $key, $limit, $compute)
+    };
+}
+
+/// Fallible cache macro with both TTL and limit support.
+/// Usage: `try_cache_with_ttl_and_limit!("namespace", "key", "500ms", 5, TtlType::Sliding, || compute_value())?`
+#[macro_export]
+macro_rules! try_cache_with_ttl_and_limit {
+    ($ns:expr, $key:expr, $ttl:expr, $limit:expr, $ttl_type:expr, $compute:expr) => {
+        $crate::parse_duration($ttl)
+            .map_err($crate::CacheError::from)
+            .and_then(|ttl| {
+                $crate::try_cache_get_with_ttl_and_limit(
+                    $ns, $key, ttl, $limit, $ttl_type, $compute,
+                )
+            })
+    };
+}
+
+/// Composite key macro.
+/// Usage: `cache_key!(user_id, "profile", version)`
+///
+/// Formats each component with `Display` and joins them with ':'. Inside a
+/// component '\' becomes "\\" and ':' becomes "\:", so `cache_key!("a:b", "c")`
+/// and `cache_key!("a", "b:c")` give different keys. `#[memoize]` builds its
+/// keys the same way.
+#[macro_export]
+macro_rules! cache_key {
+    ($($part:expr),+ $(,)?) => {
+        $crate::__private::memoize_key(&[$($part.to_string()),+])
+    };
+}
@@ -0,0 +1,199 @@
+use crate::cache::{
+    cache_clear_namespace, cache_configure_namespace, cache_get, cache_get_with_limit,
+    cache_get_with_ttl, cache_invalidate, cache_invalidate_prefix, cache_stats_for_namespace,
+    try_cache_get, EvictionPolicy, TtlType,
+};
+use crate::error::CacheError;
+use crate::stats::CacheStats;
+use std::fmt;
+use std::time::Duration;
+
+/// A key for the global cache functions
+///
+/// Implemented for strings and integers; implement it for your own key types
+/// to pass them to `Namespace` methods without formatting them by hand.
+pub trait CacheKey {
+    /// Returns the string the entry is stored under
+    fn cache_key(&self) -> String;
+}
+
+impl CacheKey for str {
+    fn cache_key(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl CacheKey for String {
+    fn cache_key(&self) -> String {
+        self.clone()
+    }
+}
+
+impl<T: CacheKey + ?Sized> CacheKey for &T {
+    fn cache_key(&self) -> String {
+        (**self).cache_key()
+    }
+}
+
+macro_rules! impl_cache_key_for_integers {
+    ($($ty:ty),*) => {
+        $(impl CacheKey for $ty {
+            fn cache_key(&self) -> String {
+                self.to_string()
+            }
+        })*
+    };
+}
+
+impl_cache_key_for_integers!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// A typed handle to a namespace in the global registry
+///
+/// Declaring namespaces once as constants catches typos at compile time
+/// instead of silently creating a second cache:
+///
+/// ```
+/// use fondue::Namespace;
+///
+/// const USERS: Namespace = Namespace::new("users");
+///
+/// // Before: fondue::cache_get("users", &id.to_string(), || lookup(id))
+/// let id = 42u64;
+/// let name: String = USERS.get(&id, || format!("user {}", id));
+/// assert_eq!(name, "user 42");
+/// ```
+///
+/// An enum of namespaces can convert into `Namespace`:
+///
+/// ```
+/// use fondue::Namespace;
+///
+/// enum Ns {
+///     Users,
+///     Sessions,
+/// }
+///
+/// impl From<Ns> for Namespace {
+///     fn from(ns: Ns) -> Self {
+///         match ns {
+///             Ns::Users => Namespace::new("users"),
+///             Ns::Sessions => Namespace::new("sessions"),
+///         }
+///     }
+/// }
+///
+/// let hits: u32 = Namespace::from(Ns::Sessions).get("abc", || 1);
+/// assert_eq!(hits, 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Namespace(&'static str);
+
+impl Namespace {
+    /// Creates a handle for the namespace `name`
+    pub const fn new(name: &'static str) -> Self {
+        Namespace(name)
+    }
+
+    /// Returns the namespace name
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+
+    /// Sets this namespace's eviction policy; see `cache_configure_namespace`
+    pub fn configure(&self, policy: EvictionPolicy) {
+        cache_configure_namespace(self.0, policy);
+    }
+
+    /// Retrieves a cached value or computes it; see `cache_get`
+    pub fn get<K, F, V>(&self, key: &K, compute: F) -> V
+    where
+        K: CacheKey + ?Sized,
+        F: FnOnce() -> V,
+        V: Clone + ToString + std::str::FromStr,
+        V::Err: std::fmt::Debug,
+    {
+        cache_get(self.0, &key.cache_key(), compute)
+    }
+
+    /// Retrieves a cached value or computes it with a TTL; see `cache_get_with_ttl`
+    pub fn get_with_ttl<K, F, V>(&self, key: &K, ttl: Duration, ttl_type: TtlType, compute: F) -> V
+    where
+        K: CacheKey + ?Sized,
+        F: FnOnce() -> V,
+        V: Clone + ToString + std::str::FromStr,
+        V::Err: std::fmt::Debug,
+    {
+        cache_get_with_ttl(self.0, &key.cache_key(), ttl, ttl_type, compute)
+    }
+
+    /// Retrieves a cached value or computes it with an LRU limit; see `cache_get_with_limit`
+    pub fn get_with_limit<K, F, V>(&self, key: &K, limit: usize, compute: F) -> V
+    where
+        K: CacheKey + ?Sized,
+        F: FnOnce() -> V,
+        V: Clone + ToString + std::str::FromStr,
+        V::Err: std::fmt::Debug,
+    {
+        cache_get_with_limit(self.0, &key.cache_key(), limit, compute)
+    }
+
+    /// Fallible variant of `get`; see `try_cache_get`
+    pub fn try_get<K, F, V>(&self, key: &K, compute: F) -> Result<V, CacheError>
+    where
+        K: CacheKey + ?Sized,
+        F: FnOnce() -> V,
+        V: Clone + ToString + std::str::FromStr,
+        V::Err: std::fmt::Debug,
+    {
+        try_cache_get(self.0, &key.cache_key(), compute)
+    }
+
+    /// Removes one key from this namespace
+    pub fn invalidate<K: CacheKey + ?Sized>(&self, key: &K) -> bool {
+        cache_invalidate(self.0, &key.cache_key())
+    }
+
+    /// Removes every key starting with `prefix` from this namespace
+    pub fn invalidate_prefix(&self, prefix: &str) -> usize {
+        cache_invalidate_prefix(self.0, prefix)
+    }
+
+    /// Removes every entry in this namespace
+    pub fn clear(&self) {
+        cache_clear_namespace(self.0);
+    }
+
+    /// Returns this namespace's stats; see `cache_stats_for_namespace`
+    pub fn stats(&self) -> CacheStats {
+        cache_stats_for_namespace(self.0)
+    }
+}
+
+impl AsRef<str> for Namespace {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDERS: Namespace = Namespace::new("typed_orders");
+
+    #[test]
+    fn test_typed_namespace_shares_string_api_cache() {
+        let total: u64 = ORDERS.get(&7u32, || 70);
+        assert_eq!(total, 70);
+        let cached: u64 = cache_get("typed_orders", "7", || 0);
+        assert_eq!(cached, 70);
+        assert!(ORDERS.invalidate(&7u32));
+        assert_eq!(ORDERS.stats().entries, 0);
+    }
+}
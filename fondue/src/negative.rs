@@ -0,0 +1,172 @@
+use crate::cache::{Cache, EvictionPolicy, TtlType};
+use std::hash::Hash;
+use std::time::Duration;
+
+/// Cache that also remembers "not found" results for a shorter TTL
+///
+/// Found values live in a regular `Cache` governed by the configured policy.
+/// Absent results are recorded in a separate TTL-only cache, so they expire on
+/// their own (usually shorter) schedule and never take up room under the
+/// positive cache's LRU limit.
+///
+/// A caller tells the three states apart through `get_if_cached`:
+/// `None` is a cache miss, `Some(None)` is a cached negative and
+/// `Some(Some(value))` is a cached value.
+pub struct NegativeCache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    positive: Cache<K, V>,
+    negative: Cache<K, ()>,
+}
+
+impl<K, V> NegativeCache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Creates a cache using `policy` for found values and `negative_ttl` for absent ones
+    pub fn new(policy: EvictionPolicy, negative_ttl: Duration) -> Self {
+        Self {
+            positive: Cache::with_policy(policy),
+            negative: Cache::with_policy(EvictionPolicy::Ttl {
+                duration: negative_ttl,
+                ttl_type: TtlType::Fixed,
+            }),
+        }
+    }
+
+    /// Wraps existing caches for found values and absent markers, keeping their configuration
+    ///
+    /// `negative` should expire its entries on its own, e.g. under a fixed TTL
+    /// policy, or absent results are remembered until evicted. Use this to
+    /// configure either side through `Cache::builder`, for instance to share
+    /// a `ManualClock` between them.
+    pub fn from_caches(positive: Cache<K, V>, negative: Cache<K, ()>) -> Self {
+        Self { positive, negative }
+    }
+
+    /// Retrieves a cached result or computes it, caching both found and absent results
+    pub fn get<F>(&self, key: &K, compute: F) -> Option<V>
+    where
+        F: FnOnce() -> Option<V>,
+    {
+        match self.get_or_try(key, || Ok::<_, std::convert::Infallible>(compute())) {
+            Ok(result) => result,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Fallible variant of `get`; errors are returned and nothing is cached for them
+    pub fn get_or_try<F, E>(&self, key: &K, compute: F) -> Result<Option<V>, E>
+    where
+        F: FnOnce() -> Result<Option<V>, E>,
+    {
+        if let Some(cached) = self.get_if_cached(key) {
+            return Ok(cached);
+        }
+        match compute()? {
+            Some(value) => {
+                self.positive.insert(key.clone(), value.clone());
+                Ok(Some(value))
+            }
+            None => {
+                self.negative.insert(key.clone(), ());
+                Ok(None)
+            }
+        }
+    }
+
+    /// Looks up a key without computing, distinguishing a cached negative from a miss
+    pub fn get_if_cached(&self, key: &K) -> Option<Option<V>> {
+        if let Some(value) = self.positive.get_if_cached(key) {
+            return Some(Some(value));
+        }
+        self.negative.get_if_cached(key).map(|_| None)
+    }
+
+    /// Returns true if the key is currently cached as absent
+    pub fn is_negative(&self, key: &K) -> bool {
+        self.negative.get_if_cached(key).is_some()
+    }
+
+    /// Removes both the positive and negative entry for a key
+    pub fn invalidate(&self, key: &K) -> bool {
+        let positive = self.positive.invalidate(key);
+        let negative = self.negative.invalidate(key);
+        positive || negative
+    }
+
+    /// Clears all positive and negative entries
+    pub fn clear(&self) {
+        self.positive.clear();
+        self.negative.clear();
+    }
+
+    /// Returns the cache holding found values
+    pub fn positive(&self) -> &Cache<K, V> {
+        &self.positive
+    }
+
+    /// Returns the cache holding absent markers
+    pub fn negative(&self) -> &Cache<K, ()> {
+        &self.negative
+    }
+}
+
+impl<K, V> Clone for NegativeCache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            positive: self.positive.clone(),
+            negative: self.negative.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn test_negative_entries_expire_first() {
+        let clock = ManualClock::new();
+        let cache: NegativeCache<u32, String> = NegativeCache::from_caches(
+            Cache::builder()
+                .policy(EvictionPolicy::Lru(1))
+                .clock(clock.clone())
+                .build(),
+            Cache::builder()
+                .policy(EvictionPolicy::Ttl {
+                    duration: Duration::from_secs(20),
+                    ttl_type: TtlType::Fixed,
+                })
+                .clock(clock.clone())
+                .build(),
+        );
+        assert_eq!(cache.get_if_cached(&1), None);
+        assert_eq!(cache.get(&1, || None), None);
+        assert_eq!(cache.get_if_cached(&1), Some(None));
+        assert_eq!(
+            cache.get(&2, || Some("two".to_string())),
+            Some("two".to_string())
+        );
+        // The negative entry does not count against the positive LRU limit
+        assert_eq!(cache.positive().len(), 1);
+        assert!(cache.is_negative(&1));
+
+        clock.advance(Duration::from_secs(19));
+        assert!(cache.is_negative(&1));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.get_if_cached(&1), None);
+        assert_eq!(
+            cache.get(&1, || Some("one".to_string())),
+            Some("one".to_string())
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use crate::cache::{get_global_cache_storage, Cache, EvictionPolicy, RECENT_HIT_WINDOW};
+use crate::stats::{batch_stats, CacheStats};
+use crate::window;
+use dashmap::DashMap;
+use std::sync::Arc;
+
+/// Owned handle to a set of namespaced string caches
+///
+/// `CacheRegistry::global()` shares its namespaces with the `cache_*` free
+/// functions; `CacheRegistry::new()` starts an isolated set, which keeps tests
+/// from seeing each other's entries. Clones share the same namespaces.
+pub struct CacheRegistry {
+    caches: Arc<DashMap<String, Cache<String, String>>>,
+}
+
+impl CacheRegistry {
+    /// Creates an empty registry independent of the global one
+    pub fn new() -> Self {
+        Self {
+            caches: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Returns a handle to the registry behind the `cache_*` functions
+    pub fn global() -> Self {
+        Self {
+            caches: Arc::clone(get_global_cache_storage()),
+        }
+    }
+
+    /// Returns the namespace's cache, creating an unbounded one on first use
+    pub fn namespace(&self, name: &str) -> Cache<String, String> {
+        self.namespace_with_policy(name, EvictionPolicy::None)
+    }
+
+    /// Returns the namespace's cache, creating it with `policy` on first use
+    ///
+    /// An existing cache keeps its policy; use `Cache::set_policy` to change it.
+    pub fn namespace_with_policy(
+        &self,
+        name: &str,
+        policy: EvictionPolicy,
+    ) -> Cache<String, String> {
+        if let Some(cache) = self.caches.get(name) {
+            return cache.clone();
+        }
+        self.caches
+            .entry(name.to_string())
+            .or_insert_with(|| Cache::with_policy(policy))
+            .clone()
+    }
+
+    /// Returns the namespace's cache without creating it
+    pub fn get(&self, name: &str) -> Option<Cache<String, String>> {
+        self.caches.get(name).map(|cache| cache.clone())
+    }
+
+    /// Lists the namespaces, sorted
+    pub fn namespaces(&self) -> Vec<String> {
+        let mut namespaces: Vec<String> = self
+            .caches
+            .iter()
+            .map(|cache| cache.key().clone())
+            .collect();
+        namespaces.sort();
+        namespaces
+    }
+
+    /// Removes every entry in a namespace, keeping its cache and counters
+    pub fn clear_namespace(&self, name: &str) {
+        if let Some(cache) = self.get(name) {
+            cache.clear();
+        }
+    }
+
+    /// Removes a namespace entirely, returning whether it existed
+    ///
+    /// Its stats row is removed too. Handles returned earlier by `namespace`
+    /// keep working but are detached; the next use of the name creates a fresh cache.
+    pub fn drop_namespace(&self, name: &str) -> bool {
+        match self.caches.remove(name) {
+            Some((_, cache)) => {
+                cache.forget_stats();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes `key` from every namespace, returning how many entries were removed
+    pub fn invalidate_key(&self, key: &str) -> usize {
+        let key = key.to_string();
+        let caches: Vec<_> = self.caches.iter().map(|cache| cache.clone()).collect();
+        batch_stats(|| caches.iter().filter(|cache| cache.invalidate(&key)).count())
+    }
+
+    /// Removes every entry in every namespace
+    pub fn clear_all(&self) {
+        batch_stats(|| {
+            for cache in self.caches.iter() {
+                cache.value().clear();
+            }
+        });
+    }
+
+    /// Summarizes one namespace's stats; all zero if it doesn't exist
+    pub fn namespace_stats(&self, name: &str) -> CacheStats {
+        let mut stats = CacheStats::new(name);
+        if let Some(cache) = self.get(name) {
+            stats.hits = cache.hit_count();
+            stats.misses = cache.miss_count();
+            stats.entries = cache.live_len() as u64;
+            stats.bytes = cache.approx_memory_bytes();
+            stats.deduped = cache.deduped_count();
+            stats.hit_rate = window::rate(stats.hits, stats.misses);
+            stats.recent_hit_rate = Some(cache.windowed_hit_rate(RECENT_HIT_WINDOW));
+        }
+        stats
+    }
+
+    /// Combines the stats of every namespace into one summary
+    pub fn stats(&self) -> CacheStats {
+        let mut stats = CacheStats::new("CacheRegistry");
+        let (mut recent_hits, mut recent_misses) = (0, 0);
+        for cache in self.caches.iter() {
+            stats.hits += cache.hit_count();
+            stats.misses += cache.miss_count();
+            stats.entries += cache.live_len() as u64;
+            stats.bytes += cache.approx_memory_bytes();
+            stats.deduped += cache.deduped_count();
+            let (hits, misses) = cache.windowed_counts(RECENT_HIT_WINDOW);
+            recent_hits += hits;
+            recent_misses += misses;
+        }
+        stats.hit_rate = window::rate(stats.hits, stats.misses);
+        stats.recent_hit_rate = Some(window::rate(recent_hits, recent_misses));
+        stats
+    }
+}
+
+impl Default for CacheRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for CacheRegistry {
+    fn clone(&self) -> Self {
+        Self {
+            caches: Arc::clone(&self.caches),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partitions_clear_and_drop_independently() {
+        let registry = CacheRegistry::new();
+        let users = registry.namespace("users");
+        let orders = registry.namespace_with_policy("orders", EvictionPolicy::Lru(10));
+        users.get(&"a".to_string(), || "1".to_string());
+        orders.get(&"b".to_string(), || "2".to_string());
+        orders.get(&"b".to_string(), || unreachable!());
+
+        let stats = registry.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 2));
+
+        assert_eq!(registry.invalidate_key("b"), 1);
+        orders.get(&"b".to_string(), || "2".to_string());
+        let stats = registry.stats();
+        assert_eq!((stats.misses, stats.entries), (3, 2));
+
+        registry.clear_namespace("users");
+        assert!(users.is_empty());
+        assert_eq!(registry.namespace_stats("orders").entries, 1);
+
+        let orders_stats = orders.stats_name();
+        assert!(crate::get_stats(&orders_stats).is_some());
+        assert!(registry.drop_namespace("orders"));
+        assert!(crate::get_stats(&orders_stats).is_none());
+        assert_eq!(registry.namespaces(), ["users"]);
+        // Isolated from the global registry
+        assert!(!CacheRegistry::global()
+            .namespaces()
+            .contains(&"orders".to_string()));
+    }
+}
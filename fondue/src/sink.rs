@@ -0,0 +1,375 @@
+use crate::cache::{Cache, EvictionPolicy};
+use std::hash::Hash;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Backing store that cache writes are propagated to
+pub trait CacheSink<K, V> {
+    /// Persists one entry
+    fn store(&self, key: &K, value: &V);
+
+    /// Persists a batch of queued write-behind entries; defaults to one `store` per entry
+    fn store_batch(&self, entries: &[(K, V)]) {
+        for (key, value) in entries {
+            self.store(key, value);
+        }
+    }
+}
+
+/// How inserts reach the sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteMode {
+    Through, // `insert` calls the sink before returning
+    Behind {
+        batch_size: usize,
+        flush_interval: Duration,
+    }, // A background thread flushes once `batch_size` writes queue up or `flush_interval` passes
+}
+
+/// Cache whose inserts are written to a `CacheSink`
+///
+/// Created by `Cache::with_sink`. In write-behind mode the queue is flushed
+/// when the last clone is dropped, so no acknowledged write is lost on a
+/// clean shutdown; call `flush` to force it earlier. A batch the sink panics
+/// on stays queued, and the flush thread retries it an interval later.
+pub struct WriteCache<K, V, Snk>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    Snk: CacheSink<K, V>,
+{
+    cache: Cache<K, V>,
+    sink: Arc<Snk>,
+    behind: Option<Arc<Flusher<K, V, Snk>>>,
+}
+
+/// Write-behind queue shared between writers and the flush thread
+struct Queue<K, V, Snk> {
+    state: Mutex<QueueState<K, V>>,
+    ready: Condvar,
+    flushing: Mutex<()>, // Held from taking a batch until it's stored, so batches land in order
+    sink: Arc<Snk>,
+    batch_size: usize,
+}
+
+/// Entries waiting to be flushed, plus the shutdown signal
+struct QueueState<K, V> {
+    entries: Vec<(K, V)>,
+    shutdown: bool,
+}
+
+/// Owns the flush thread; dropping it flushes the queue and joins the thread
+struct Flusher<K, V, Snk>
+where
+    Snk: CacheSink<K, V>,
+{
+    queue: Arc<Queue<K, V, Snk>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    /// Creates a cache whose inserts are also written to `sink` according to `mode`
+    pub fn with_sink<Snk>(
+        policy: EvictionPolicy,
+        sink: Snk,
+        mode: WriteMode,
+    ) -> WriteCache<K, V, Snk>
+    where
+        Snk: CacheSink<K, V> + Send + Sync + 'static,
+    {
+        let sink = Arc::new(sink);
+        let behind = match mode {
+            WriteMode::Through => None,
+            WriteMode::Behind {
+                batch_size,
+                flush_interval,
+            } => Some(Arc::new(Flusher::spawn(
+                Arc::clone(&sink),
+                batch_size.max(1),
+                flush_interval,
+            ))),
+        };
+        WriteCache {
+            cache: Cache::with_policy(policy),
+            sink,
+            behind,
+        }
+    }
+}
+
+impl<K, V, Snk> WriteCache<K, V, Snk>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    Snk: CacheSink<K, V>,
+{
+    /// Caches a value and writes it to the sink, immediately or via the write-behind queue
+    pub fn insert(&self, key: K, value: V) {
+        self.cache.insert(key.clone(), value.clone());
+        match &self.behind {
+            None => self.sink.store(&key, &value),
+            Some(flusher) => flusher.queue.push(key, value),
+        }
+    }
+
+    /// Retrieves cached value or computes and caches it; computed values are not written
+    pub fn get<F>(&self, key: &K, compute: F) -> V
+    where
+        F: FnOnce() -> V,
+    {
+        self.cache.get(key, compute)
+    }
+
+    /// Looks up a key without computing
+    pub fn get_if_cached(&self, key: &K) -> Option<V> {
+        self.cache.get_if_cached(key)
+    }
+
+    /// Removes a key from the cache; the sink is not touched
+    pub fn invalidate(&self, key: &K) -> bool {
+        self.cache.invalidate(key)
+    }
+
+    /// Writes all queued write-behind entries to the sink now
+    pub fn flush(&self) {
+        if let Some(flusher) = &self.behind {
+            flusher.queue.flush();
+        }
+    }
+
+    /// Returns the number of writes waiting for the flush thread
+    pub fn pending_writes(&self) -> usize {
+        self.behind.as_ref().map_or(0, |flusher| {
+            flusher.queue.state.lock().unwrap().entries.len()
+        })
+    }
+
+    /// Returns the underlying cache
+    pub fn cache(&self) -> &Cache<K, V> {
+        &self.cache
+    }
+
+    /// Returns the sink writes go to
+    pub fn sink(&self) -> &Snk {
+        &self.sink
+    }
+}
+
+impl<K, V, Snk> Clone for WriteCache<K, V, Snk>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    Snk: CacheSink<K, V>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            sink: Arc::clone(&self.sink),
+            behind: self.behind.clone(),
+        }
+    }
+}
+
+impl<K, V, Snk> Queue<K, V, Snk>
+where
+    Snk: CacheSink<K, V>,
+{
+    /// Queues a write, waking the flush thread once a batch is full
+    fn push(&self, key: K, value: V) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.push((key, value));
+        if state.entries.len() >= self.batch_size {
+            self.ready.notify_one();
+        }
+    }
+
+    /// Writes everything queued so far to the sink
+    ///
+    /// Flushes are serialized, so a manual flush racing the flush thread can't
+    /// store an older value for a key after a newer one. If the sink panics the
+    /// batch goes back to the front of the queue before the panic continues.
+    fn flush(&self) {
+        // A sink that panicked mid-batch poisons this; later batches still go out in order
+        let _order = self.flushing.lock().unwrap_or_else(PoisonError::into_inner);
+        let batch = std::mem::take(&mut self.state.lock().unwrap().entries);
+        if batch.is_empty() {
+            return;
+        }
+        if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(|| self.sink.store_batch(&batch)))
+        {
+            let mut state = self.state.lock().unwrap();
+            let newer = std::mem::replace(&mut state.entries, batch);
+            state.entries.extend(newer);
+            drop(state);
+            panic::resume_unwind(panic);
+        }
+    }
+}
+
+impl<K, V, Snk> Flusher<K, V, Snk>
+where
+    K: Send + 'static,
+    V: Send + 'static,
+    Snk: CacheSink<K, V> + Send + Sync + 'static,
+{
+    /// Starts the thread that flushes full batches and, every `interval`, partial ones
+    fn spawn(sink: Arc<Snk>, batch_size: usize, interval: Duration) -> Self {
+        let queue = Arc::new(Queue {
+            state: Mutex::new(QueueState {
+                entries: Vec::new(),
+                shutdown: false,
+            }),
+            ready: Condvar::new(),
+            flushing: Mutex::new(()),
+            sink,
+            batch_size,
+        });
+        let worker = Arc::clone(&queue);
+        let thread = std::thread::spawn(move || loop {
+            let mut state = worker.state.lock().unwrap();
+            while state.entries.len() < worker.batch_size && !state.shutdown {
+                let (next, timeout) = worker.ready.wait_timeout(state, interval).unwrap();
+                state = next;
+                if timeout.timed_out() {
+                    break;
+                }
+            }
+            let shutdown = state.shutdown;
+            drop(state);
+            // The thread outlives a panicking sink; its batch is requeued and retried
+            let failed = panic::catch_unwind(AssertUnwindSafe(|| worker.flush())).is_err();
+            if shutdown {
+                break;
+            }
+            if failed {
+                let state = worker.state.lock().unwrap();
+                let _ = worker
+                    .ready
+                    .wait_timeout_while(state, interval, |state| !state.shutdown)
+                    .unwrap();
+            }
+        });
+        Self {
+            queue,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl<K, V, Snk> Drop for Flusher<K, V, Snk>
+where
+    Snk: CacheSink<K, V>,
+{
+    fn drop(&mut self) {
+        self.queue.state.lock().unwrap().shutdown = true;
+        self.queue.ready.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        // Retries a batch the sink panicked on during the thread's last flush
+        self.queue.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Store {
+        rows: Mutex<Vec<(u32, u32)>>,
+        batches: Mutex<usize>,
+    }
+
+    impl CacheSink<u32, u32> for Arc<Store> {
+        fn store(&self, key: &u32, value: &u32) {
+            self.rows.lock().unwrap().push((*key, *value));
+        }
+
+        fn store_batch(&self, entries: &[(u32, u32)]) {
+            *self.batches.lock().unwrap() += 1;
+            self.rows.lock().unwrap().extend_from_slice(entries);
+        }
+    }
+
+    #[test]
+    fn test_write_through_stores_immediately() {
+        let store = Arc::new(Store::default());
+        let cache = Cache::with_sink(EvictionPolicy::None, Arc::clone(&store), WriteMode::Through);
+        cache.insert(1, 10);
+        assert_eq!(*store.rows.lock().unwrap(), [(1, 10)]);
+        assert_eq!(cache.get_if_cached(&1), Some(10));
+    }
+
+    #[test]
+    fn test_write_behind_flushes_pending_on_drop() {
+        let store = Arc::new(Store::default());
+        let mode = WriteMode::Behind {
+            batch_size: 10,
+            flush_interval: Duration::from_secs(60),
+        };
+        let cache = Cache::with_sink(EvictionPolicy::None, Arc::clone(&store), mode);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        assert_eq!(cache.pending_writes(), 2);
+        assert!(store.rows.lock().unwrap().is_empty());
+
+        cache.flush();
+        assert_eq!(*store.rows.lock().unwrap(), [(1, 10), (2, 20)]);
+        assert_eq!(*store.batches.lock().unwrap(), 1);
+
+        cache.insert(3, 30);
+        drop(cache);
+        assert_eq!(*store.rows.lock().unwrap(), [(1, 10), (2, 20), (3, 30)]);
+    }
+
+    #[test]
+    fn test_write_behind_retries_batch_after_sink_panics() {
+        struct Flaky {
+            store: Arc<Store>,
+            failed: std::sync::atomic::AtomicBool,
+        }
+
+        impl CacheSink<u32, u32> for Flaky {
+            fn store(&self, key: &u32, value: &u32) {
+                self.store.store(key, value);
+            }
+
+            fn store_batch(&self, entries: &[(u32, u32)]) {
+                if !self.failed.swap(true, std::sync::atomic::Ordering::Relaxed) {
+                    panic!("sink unavailable");
+                }
+                self.store.store_batch(entries);
+            }
+        }
+
+        let store = Arc::new(Store::default());
+        let sink = Flaky {
+            store: Arc::clone(&store),
+            failed: Default::default(),
+        };
+        let mode = WriteMode::Behind {
+            batch_size: 1,
+            flush_interval: Duration::from_millis(10),
+        };
+        let cache = Cache::with_sink(EvictionPolicy::None, sink, mode);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        // The first batch panics; the thread survives and stores it on retry, in order
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while store.rows.lock().unwrap().len() < 2 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(*store.rows.lock().unwrap(), [(1, 10), (2, 20)]);
+        assert_eq!(cache.pending_writes(), 0);
+        cache.insert(3, 30);
+        drop(cache);
+        assert_eq!(store.rows.lock().unwrap().last(), Some(&(3, 30)));
+    }
+}
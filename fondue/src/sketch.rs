@@ -0,0 +1,112 @@
+/// Rows in the sketch; each key maps to one counter per row
+const DEPTH: usize = 4;
+
+/// Counters saturate here, so one row entry fits in a byte
+const MAX_COUNT: u8 = 15;
+
+/// Odd multipliers giving each row an independent index for the same hash
+const SEEDS: [u64; DEPTH] = [
+    0x9E37_79B9_7F4A_7C15,
+    0xC2B2_AE3D_27D4_EB4F,
+    0x1656_67B1_9E37_79F9,
+    0x85EB_CA77_C2B2_AE63,
+];
+
+/// Approximate access counts used for TinyLFU admission
+///
+/// A count-min sketch: a key's estimate is the smallest of its counters, so
+/// collisions can only overestimate. Once the number of increments reaches ten
+/// times the width every counter is halved, letting old popularity fade.
+pub(crate) struct FrequencySketch {
+    rows: [Vec<u8>; DEPTH],
+    mask: usize,
+    additions: usize,
+    sample_size: usize,
+}
+
+impl FrequencySketch {
+    /// Creates a sketch sized for a cache holding about `capacity` entries
+    ///
+    /// Rows are a few times wider than the capacity so that keys streaming
+    /// through a full cache rarely share counters with the entries it holds.
+    pub(crate) fn new(capacity: usize) -> Self {
+        let width = Self::width_for(capacity);
+        Self {
+            rows: std::array::from_fn(|_| vec![0; width]),
+            mask: width - 1,
+            additions: 0,
+            sample_size: width * 10,
+        }
+    }
+
+    /// Checks whether this sketch has the width `new(capacity)` would give
+    pub(crate) fn fits(&self, capacity: usize) -> bool {
+        self.mask + 1 == Self::width_for(capacity)
+    }
+
+    /// Row width for a cache holding about `capacity` entries
+    fn width_for(capacity: usize) -> usize {
+        capacity
+            .saturating_mul(4)
+            .clamp(64, 1 << 24)
+            .next_power_of_two()
+    }
+
+    /// Records one access to the key with this hash
+    pub(crate) fn increment(&mut self, hash: u64) {
+        for row in 0..DEPTH {
+            let index = self.index(hash, row);
+            let counter = &mut self.rows[row][index];
+            *counter = (*counter + 1).min(MAX_COUNT);
+        }
+        self.additions += 1;
+        if self.additions >= self.sample_size {
+            self.age();
+        }
+    }
+
+    /// Estimates how often the key with this hash was accessed recently
+    pub(crate) fn estimate(&self, hash: u64) -> u8 {
+        (0..DEPTH)
+            .map(|row| self.rows[row][self.index(hash, row)])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Halves every counter
+    fn age(&mut self) {
+        for row in &mut self.rows {
+            for counter in row.iter_mut() {
+                *counter /= 2;
+            }
+        }
+        self.additions /= 2;
+    }
+
+    /// Maps a hash to its counter in `row`
+    fn index(&self, hash: u64, row: usize) -> usize {
+        let mixed = hash.wrapping_mul(SEEDS[row]);
+        ((mixed >> 32) ^ mixed) as usize & self.mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimates_saturate_and_age() {
+        let mut sketch = FrequencySketch::new(16);
+        assert_eq!(sketch.rows[0].len(), 64);
+        for _ in 0..20 {
+            sketch.increment(42);
+        }
+        assert_eq!(sketch.estimate(42), MAX_COUNT);
+        assert_eq!(sketch.estimate(7), 0);
+        // Reaching the sample size halves everything
+        for hash in 0..620 {
+            sketch.increment(1_000 + hash);
+        }
+        assert!(sketch.estimate(42) < MAX_COUNT);
+    }
+}
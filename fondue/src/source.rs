@@ -0,0 +1,143 @@
+use crate::cache::{Cache, EvictionPolicy};
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Backing store that a read-through cache loads missing values from
+///
+/// Implemented for any `Fn(&K) -> V`, so a closure works as a source too.
+pub trait CacheSource<K, V> {
+    /// Loads the value for `key`; called on every cache miss
+    fn load(&self, key: &K) -> V;
+}
+
+impl<K, V, F> CacheSource<K, V> for F
+where
+    F: Fn(&K) -> V,
+{
+    fn load(&self, key: &K) -> V {
+        self(key)
+    }
+}
+
+/// Cache that loads misses from a fixed `CacheSource` instead of a per-call closure
+///
+/// Created by `Cache::with_source`. The underlying `Cache` stays available via
+/// `cache()` for the closure-based API and statistics.
+pub struct ReadThroughCache<K, V, Src>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    cache: Cache<K, V>,
+    source: Arc<Src>,
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Creates a read-through cache whose `get` loads misses from `source`
+    pub fn with_source<Src>(policy: EvictionPolicy, source: Src) -> ReadThroughCache<K, V, Src>
+    where
+        Src: CacheSource<K, V>,
+    {
+        ReadThroughCache {
+            cache: Cache::with_policy(policy),
+            source: Arc::new(source),
+        }
+    }
+}
+
+impl<K, V, Src> ReadThroughCache<K, V, Src>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    Src: CacheSource<K, V>,
+{
+    /// Retrieves the cached value or loads it from the source
+    pub fn get(&self, key: &K) -> V {
+        self.cache.get(key, || self.source.load(key))
+    }
+
+    /// Reloads a key from the source, replacing any cached value
+    pub fn reload(&self, key: &K) -> V {
+        let value = self.source.load(key);
+        self.cache.insert(key.clone(), value.clone());
+        value
+    }
+
+    /// Looks up a key without loading it
+    pub fn get_if_cached(&self, key: &K) -> Option<V> {
+        self.cache.get_if_cached(key)
+    }
+
+    /// Removes a key so the next `get` loads it again
+    pub fn invalidate(&self, key: &K) -> bool {
+        self.cache.invalidate(key)
+    }
+
+    /// Removes all cached values
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+
+    /// Returns the underlying cache
+    pub fn cache(&self) -> &Cache<K, V> {
+        &self.cache
+    }
+
+    /// Returns the source values are loaded from
+    pub fn source(&self) -> &Src {
+        &self.source
+    }
+}
+
+impl<K, V, Src> Clone for ReadThroughCache<K, V, Src>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            source: Arc::clone(&self.source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Table {
+        rows: HashMap<u32, String>,
+        loads: AtomicUsize,
+    }
+
+    impl CacheSource<u32, Option<String>> for Table {
+        fn load(&self, key: &u32) -> Option<String> {
+            self.loads.fetch_add(1, Ordering::SeqCst);
+            self.rows.get(key).cloned()
+        }
+    }
+
+    #[test]
+    fn test_read_through_loads_once() {
+        let table = Table {
+            rows: HashMap::from([(1, "one".to_string())]),
+            loads: AtomicUsize::new(0),
+        };
+        let cache = Cache::with_source(EvictionPolicy::None, table);
+        assert_eq!(cache.get(&1), Some("one".to_string()));
+        assert_eq!(cache.get(&1), Some("one".to_string()));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.source().loads.load(Ordering::SeqCst), 2);
+
+        let doubled: ReadThroughCache<u32, u32, _> =
+            Cache::with_source(EvictionPolicy::None, |key: &u32| key * 2);
+        assert_eq!(doubled.get(&21), 42);
+    }
+}
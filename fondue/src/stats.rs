@@ -1,14 +1,26 @@
+use crate::cache::RECENT_HIT_WINDOW;
+use crate::window::{self, HitWindow};
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Statistics for a single cache or context
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CacheStats {
     pub name: String,
     pub hits: u64,
     pub misses: u64,
     pub entries: u64,
     pub hit_rate: f64,
+    // Hit rate over the last minute, when the source tracks one
+    pub recent_hit_rate: Option<f64>,
+    pub bytes: u64,
+    // Callers served by another caller's in-flight computation instead of their own
+    pub deduped: u64,
 }
 
 impl CacheStats {
@@ -20,6 +32,9 @@ impl CacheStats {
             misses: 0,
             entries: 0,
             hit_rate: 0.0,
+            recent_hit_rate: None,
+            bytes: 0,
+            deduped: 0,
         }
     }
 
@@ -28,6 +43,24 @@ impl CacheStats {
         self.hits + self.misses
     }
 
+    /// Returns the activity since `previous`, an earlier reading of the same cache
+    ///
+    /// Counters (`hits`, `misses`, `deduped`) become differences, clamped at
+    /// zero if they were reset in between, and `hit_rate` is recomputed over
+    /// the interval. Gauges (`entries`, `bytes`, `recent_hit_rate`) keep this
+    /// reading's values.
+    pub fn delta(&self, previous: &CacheStats) -> CacheStats {
+        let hits = self.hits.saturating_sub(previous.hits);
+        let misses = self.misses.saturating_sub(previous.misses);
+        CacheStats {
+            hits,
+            misses,
+            hit_rate: crate::window::rate(hits, misses),
+            deduped: self.deduped.saturating_sub(previous.deduped),
+            ..self.clone()
+        }
+    }
+
     /// Prints human-readable cache statistics
     pub fn print(&self) {
         println!("Cache Stats: {}", self.name);
@@ -35,10 +68,30 @@ impl CacheStats {
         println!("  Hits:        {}", self.hits);
         println!("  Misses:      {}", self.misses);
         println!("  Hit Rate:    {:.2}%", self.hit_rate * 100.0);
+        if let Some(recent) = self.recent_hit_rate {
+            println!("  Recent Rate: {:.2}%", recent * 100.0);
+        }
+        println!("  Bytes:       {}", self.bytes);
+        println!("  Deduped:     {}", self.deduped);
         println!("  Total Reqs:  {}", self.total_requests());
     }
 
+    /// Serializes the cache statistics to a JSON value, including `total_requests`
+    #[cfg(feature = "serde")]
+    pub fn to_json_value(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).expect("CacheStats is always serializable");
+        value["total_requests"] = self.total_requests().into();
+        value
+    }
+
     /// Serializes the cache statistics to a JSON string
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_json_value()).expect("JSON values always serialize")
+    }
+
+    /// Serializes the cache statistics to a JSON string
+    #[cfg(not(feature = "serde"))]
     pub fn to_json(&self) -> String {
         format!(
             r#"{{
@@ -47,6 +100,9 @@ impl CacheStats {
   "misses": {},
   "entries": {},
   "hit_rate": {:.4},
+  "recent_hit_rate": {},
+  "bytes": {},
+  "deduped": {},
   "total_requests": {}
 }}"#,
             self.name,
@@ -54,106 +110,344 @@ impl CacheStats {
             self.misses,
             self.entries,
             self.hit_rate,
+            self.recent_hit_rate
+                .map_or_else(|| "null".to_string(), |rate| format!("{:.4}", rate)),
+            self.bytes,
+            self.deduped,
             self.total_requests()
         )
     }
 }
 
+/// Percentiles of recent `compute()` durations for one cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LatencyStats {
+    pub samples: u64,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// Column to order the stats table by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Entries,
+    Hits,
+    Misses,
+    HitRate,
+}
+
 /// Global statistics manager to track multiple caches
+///
+/// Backed by a sharded `DashMap`, so caches publishing at the same time only
+/// contend when their names land in the same shard.
 pub struct GlobalStats {
-    stats: Arc<Mutex<HashMap<String, CacheStats>>>,
+    stats: Arc<DashMap<String, Row>>,
+}
+
+/// A cache's stored stats and the cache instance that published them
+struct Row {
+    owner: Option<u64>, // The publishing cache's id; None when registered by hand
+    stats: CacheStats,
+    window: Option<Arc<HitWindow>>, // The publishing cache's hits, read for `recent_hit_rate`
+}
+
+impl Row {
+    /// Returns `recent_hit_rate`, read from the publishing cache's window if it has one
+    ///
+    /// Caches don't compute it on every operation; it's read when stats are.
+    fn recent_hit_rate(&self) -> Option<f64> {
+        match &self.window {
+            Some(window) => {
+                let (hits, misses) = window.counts(RECENT_HIT_WINDOW);
+                Some(window::rate(hits, misses))
+            }
+            None => self.stats.recent_hit_rate,
+        }
+    }
+
+    /// Brings the stored `recent_hit_rate` up to date
+    fn refresh(&mut self) {
+        self.stats.recent_hit_rate = self.recent_hit_rate();
+    }
+
+    /// Returns a copy of the stats with `recent_hit_rate` up to date
+    fn current(&self) -> CacheStats {
+        CacheStats {
+            recent_hit_rate: self.recent_hit_rate(),
+            ..self.stats.clone()
+        }
+    }
+}
+
+impl From<CacheStats> for Row {
+    fn from(stats: CacheStats) -> Self {
+        Self {
+            owner: None,
+            stats,
+            window: None,
+        }
+    }
 }
 
 impl GlobalStats {
     /// Creates a new empty GlobalStats
     pub fn new() -> Self {
         Self {
-            stats: Arc::new(Mutex::new(HashMap::new())),
+            stats: Arc::new(DashMap::new()),
         }
     }
 
     /// Registers new stats under a given name
     pub fn register(&self, name: impl Into<String>, stats: CacheStats) {
-        let mut global_stats = self.stats.lock().unwrap();
-        global_stats.insert(name.into(), stats);
+        self.stats.insert(name.into(), stats.into());
+    }
+
+    /// Registers several caches' stats in one call
+    pub fn register_many<I>(&self, entries: I)
+    where
+        I: IntoIterator<Item = (String, CacheStats)>,
+    {
+        for (name, stats) in entries {
+            self.stats.insert(name, stats.into());
+        }
     }
 
     /// Updates existing stats under the given name
     pub fn update(&self, name: &str, stats: CacheStats) {
-        let mut global_stats = self.stats.lock().unwrap();
-        global_stats.insert(name.to_string(), stats);
+        self.stats.insert(name.to_string(), stats.into());
     }
 
     /// Retrieves stats by name, if present
     pub fn get(&self, name: &str) -> Option<CacheStats> {
-        let stats = self.stats.lock().unwrap();
-        stats.get(name).cloned()
+        self.stats.get(name).map(|row| row.current())
     }
 
     /// Returns all stored statistics as a HashMap
     pub fn all(&self) -> HashMap<String, CacheStats> {
-        let stats = self.stats.lock().unwrap();
-        stats.clone()
+        self.snapshot()
+    }
+
+    /// Calls `f` on every cache's stats in place, without copying them
+    ///
+    /// For exporters that only read and format. Each shard is locked while
+    /// its stats are visited, so `f` must not record, remove or read stats
+    /// or it deadlocks; use `all` when an owned copy is needed.
+    pub fn for_each<F>(&self, mut f: F)
+    where
+        F: FnMut(&CacheStats),
+    {
+        for mut row in self.stats.iter_mut() {
+            row.refresh();
+            f(&row.stats);
+        }
+    }
+
+    /// Returns a copy of every cache's stats
+    ///
+    /// This is not a point-in-time copy of the whole registry: shards are
+    /// copied one at a time, so different caches' readings may be taken at
+    /// slightly different moments. Each row is coherent, though. A
+    /// `CacheStats` is written whole and a cache only ever replaces its own
+    /// row with a newer reading, so `hit_rate` always matches `hits` and
+    /// `misses` and a cache's counters never go backwards between snapshots.
+    pub fn snapshot(&self) -> HashMap<String, CacheStats> {
+        self.stats
+            .iter()
+            .map(|row| (row.key().clone(), row.current()))
+            .collect()
+    }
+
+    /// Stores cache `owner`'s reading unless that cache already stored a newer one (more requests)
+    ///
+    /// Concurrent callers of `update_cache_stats` can finish out of order; this
+    /// keeps the later reading from being overwritten by an earlier one. A
+    /// reading from a different cache always replaces the row, so a new cache
+    /// reusing a dropped one's name isn't hidden behind the old counts.
+    ///
+    /// `window` is the cache's hit window, kept with the row so
+    /// `recent_hit_rate` can be read when stats are rather than on every
+    /// operation; it's only cloned when the row changes hands.
+    pub(crate) fn record(
+        &self,
+        name: String,
+        owner: u64,
+        window: &Arc<HitWindow>,
+        stats: CacheStats,
+    ) {
+        match self.stats.entry(name) {
+            Entry::Occupied(mut current) => {
+                let row = current.get_mut();
+                if row.owner != Some(owner) {
+                    *row = Row {
+                        owner: Some(owner),
+                        stats,
+                        window: Some(Arc::clone(window)),
+                    };
+                } else if row.stats.total_requests() <= stats.total_requests() {
+                    row.stats = stats;
+                }
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(Row {
+                    owner: Some(owner),
+                    stats,
+                    window: Some(Arc::clone(window)),
+                });
+            }
+        }
+    }
+
+    /// `record` for several readings
+    pub(crate) fn record_many<I>(&self, readings: I)
+    where
+        I: IntoIterator<Item = (String, Reading)>,
+    {
+        for (name, (owner, window, stats)) in readings {
+            self.record(name, owner, &window, stats);
+        }
+    }
+
+    /// Stores cache `owner`'s reading even if its stored one is newer
+    pub(crate) fn replace(
+        &self,
+        name: String,
+        owner: u64,
+        window: &Arc<HitWindow>,
+        stats: CacheStats,
+    ) {
+        self.stats.insert(
+            name,
+            Row {
+                owner: Some(owner),
+                stats,
+                window: Some(Arc::clone(window)),
+            },
+        );
     }
 
     /// Prints detailed stats for all caches, or a message if none available
     pub fn print_all(&self) {
-        let stats = self.stats.lock().unwrap();
+        let stats = self.sorted(SortKey::Name, false);
         if stats.is_empty() {
             println!("No cache statistics available");
             return;
         }
         println!("= Fondue Cache Statistics =");
-        for stat in stats.values() {
+        for stat in &stats {
             stat.print();
             println!();
         }
     }
 
-    /// Prints a formatted table summary of all cache stats, or a message if none available
+    /// Prints a formatted table summary of all cache stats sorted by name, or a message if none available
     pub fn print_table(&self) {
-        let stats = self.stats.lock().unwrap();
+        self.print_table_sorted(SortKey::Name, false);
+    }
+
+    /// Prints the table summary with rows ordered by `by`, highest first when `descending`
+    pub fn print_table_sorted(&self, by: SortKey, descending: bool) {
+        let stats = self.sorted(by, descending);
         if stats.is_empty() {
             println!("No cache statistics available");
             return;
         }
-        println!("┌─────────────────────────┬─────────┬──────┬────────┬──────────┬───────────┐");
-        println!("│ Cache Name              │ Entries │ Hits │ Misses │ Hit Rate │ Total Req │");
-        println!("├─────────────────────────┼─────────┼──────┼────────┼──────────┼───────────┤");
-        for stat in stats.values() {
+        println!("┌─────────────────────────┬─────────┬──────┬────────┬──────────┬───────────┬────────────┐");
+        println!("│ Cache Name              │ Entries │ Hits │ Misses │ Hit Rate │ Total Req │ Bytes      │");
+        println!("├─────────────────────────┼─────────┼──────┼────────┼──────────┼───────────┼────────────┤");
+        for stat in &stats {
             println!(
-                "│ {:<23} │ {:>7} │ {:>4} │ {:>6} │ {:>7.2}% │ {:>9} │",
+                "│ {:<23} │ {:>7} │ {:>4} │ {:>6} │ {:>7.2}% │ {:>9} │ {:>10} │",
                 truncate_string(&stat.name, 23),
                 stat.entries,
                 stat.hits,
                 stat.misses,
                 stat.hit_rate * 100.0,
-                stat.total_requests()
+                stat.total_requests(),
+                stat.bytes
             );
         }
-        println!("└─────────────────────────┴─────────┴──────┴────────┴──────────┴───────────┘");
+        println!("└─────────────────────────┴─────────┴──────┴────────┴──────────┴───────────┴────────────┘");
+    }
+
+    /// Returns all stats ordered by `by`, ties broken by name
+    fn sorted(&self, by: SortKey, descending: bool) -> Vec<CacheStats> {
+        let mut stats: Vec<CacheStats> = self.stats.iter().map(|row| row.current()).collect();
+        stats.sort_by(|a, b| {
+            let order = match by {
+                SortKey::Name => a.name.cmp(&b.name),
+                SortKey::Entries => a.entries.cmp(&b.entries),
+                SortKey::Hits => a.hits.cmp(&b.hits),
+                SortKey::Misses => a.misses.cmp(&b.misses),
+                SortKey::HitRate => a.hit_rate.total_cmp(&b.hit_rate),
+            };
+            let order = if descending { order.reverse() } else { order };
+            order.then_with(|| a.name.cmp(&b.name))
+        });
+        stats
+    }
+
+    /// Serializes all stats to a JSON array value
+    #[cfg(feature = "serde")]
+    pub fn to_json_value(&self) -> serde_json::Value {
+        self.stats
+            .iter()
+            .map(|row| row.current().to_json_value())
+            .collect()
+    }
+
+    /// Serializes all stats to a JSON array string
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_json_value()).expect("JSON values always serialize")
     }
 
     /// Serializes all stats to a JSON array string
+    #[cfg(not(feature = "serde"))]
     pub fn to_json(&self) -> String {
-        let stats = self.stats.lock().unwrap();
         let mut json_parts = Vec::new();
-        for stat in stats.values() {
-            json_parts.push(stat.to_json());
+        for row in self.stats.iter() {
+            json_parts.push(row.current().to_json());
         }
         format!("[\n{}\n]", json_parts.join(",\n"))
     }
 
+    /// Serializes all stats to CSV with a header row and one line per cache
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("name,entries,hits,misses,hit_rate,total_requests\n");
+        for row in self.stats.iter() {
+            let stat = &row.stats;
+            csv.push_str(&format!(
+                "{},{},{},{},{:.4},{}\n",
+                csv_escape(&stat.name),
+                stat.entries,
+                stat.hits,
+                stat.misses,
+                stat.hit_rate,
+                stat.total_requests()
+            ));
+        }
+        csv
+    }
+
     /// Aggregates stats from all caches into a combined CacheStats
     pub fn aggregate(&self) -> CacheStats {
-        let stats = self.stats.lock().unwrap();
         let mut total_hits = 0;
         let mut total_misses = 0;
         let mut total_entries = 0;
-        for stat in stats.values() {
+        let mut total_bytes = 0;
+        let mut total_deduped = 0;
+        for row in self.stats.iter() {
+            let stat = &row.stats;
             total_hits += stat.hits;
             total_misses += stat.misses;
             total_entries += stat.entries;
+            total_bytes += stat.bytes;
+            total_deduped += stat.deduped;
         }
         let total_requests = total_hits + total_misses;
         let hit_rate = if total_requests > 0 {
@@ -167,19 +461,20 @@ impl GlobalStats {
             misses: total_misses,
             entries: total_entries,
             hit_rate,
+            recent_hit_rate: None,
+            bytes: total_bytes,
+            deduped: total_deduped,
         }
     }
 
     /// Clears all stored cache statistics
     pub fn clear(&self) {
-        let mut stats = self.stats.lock().unwrap();
-        stats.clear();
+        self.stats.clear();
     }
 
     /// Removes stats for a specific cache by name
     pub fn remove(&self, name: &str) -> Option<CacheStats> {
-        let mut stats = self.stats.lock().unwrap();
-        stats.remove(name)
+        self.stats.remove(name).map(|(_, row)| row.stats)
     }
 }
 
@@ -209,6 +504,11 @@ pub fn print_stats_table() {
     get_global_stats().print_table();
 }
 
+/// Prints all stats in table format, ordered by `by`
+pub fn print_stats_table_sorted(by: SortKey, descending: bool) {
+    get_global_stats().print_table_sorted(by, descending);
+}
+
 /// Gets stats for a specific cache by name, if available
 pub fn get_stats(name: &str) -> Option<CacheStats> {
     get_global_stats().get(name)
@@ -219,6 +519,17 @@ pub fn export_json() -> String {
     get_global_stats().to_json()
 }
 
+/// Exports all stats as a JSON value for embedding in a larger document
+#[cfg(feature = "serde")]
+pub fn export_json_value() -> serde_json::Value {
+    get_global_stats().to_json_value()
+}
+
+/// Exports all stats in CSV format
+pub fn export_csv() -> String {
+    get_global_stats().to_csv()
+}
+
 /// Aggregates stats from all caches into one summary
 pub fn aggregate_stats() -> CacheStats {
     get_global_stats().aggregate()
@@ -229,6 +540,57 @@ pub fn clear_stats() {
     get_global_stats().clear();
 }
 
+/// Returns a copy of all stats; see `GlobalStats::snapshot` for its consistency guarantees
+pub fn stats_snapshot() -> HashMap<String, CacheStats> {
+    get_global_stats().snapshot()
+}
+
+thread_local! {
+    /// Readings held back by `batch_stats` on this thread, latest per cache
+    static DEFERRED: RefCell<Option<HashMap<String, Reading>>> = const { RefCell::new(None) };
+}
+
+/// A cache's stats reading: its id, its hit window and its stats
+pub(crate) type Reading = (u64, Arc<HitWindow>, CacheStats);
+
+/// Records cache `owner`'s own reading, keeping its newest one
+pub(crate) fn record_stats(name: String, owner: u64, window: &Arc<HitWindow>, stats: CacheStats) {
+    let reading = DEFERRED.with_borrow_mut(|deferred| match deferred {
+        Some(deferred) => {
+            deferred.insert(name, (owner, Arc::clone(window), stats));
+            None
+        }
+        None => Some((name, stats)),
+    });
+    if let Some((name, stats)) = reading {
+        get_global_stats().record(name, owner, window, stats);
+    }
+}
+
+/// Runs `f`, publishing the stats readings it records together at the end
+///
+/// Operations touching many caches call this so each cache's stats are
+/// written once, with its final reading. Nested calls join the outermost batch.
+pub(crate) fn batch_stats<T>(f: impl FnOnce() -> T) -> T {
+    /// Publishes the batch even if `f` panics
+    struct Flush;
+
+    impl Drop for Flush {
+        fn drop(&mut self) {
+            if let Some(readings) = DEFERRED.with_borrow_mut(Option::take) {
+                get_global_stats().record_many(readings);
+            }
+        }
+    }
+
+    let outermost = DEFERRED.with_borrow_mut(|deferred| {
+        deferred.is_none() && deferred.insert(HashMap::new()).is_empty()
+    });
+    // Only the outermost call flushes
+    let _flush = if outermost { Some(Flush) } else { None };
+    f()
+}
+
 /// Registers new stats globally with given name
 pub fn register_stats(name: impl Into<String>, stats: CacheStats) {
     get_global_stats().register(name, stats);
@@ -239,6 +601,15 @@ pub fn update_stats(name: &str, stats: CacheStats) {
     get_global_stats().update(name, stats);
 }
 
+/// Quotes a CSV field if it contains a comma, quote or line break, doubling inner quotes
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
 /// Utility to truncate strings with "…" suffix if over max length
 fn truncate_string(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
@@ -266,6 +637,31 @@ mod tests {
         assert!(json.contains("\"hits\": 80"));
     }
 
+    #[test]
+    fn test_batch_stats_publishes_at_the_end() {
+        let mut stats = CacheStats::new("batched");
+        stats.hits = 3;
+        batch_stats(|| {
+            let window = Arc::new(HitWindow::new());
+            record_stats(
+                "batched".to_string(),
+                1,
+                &window,
+                CacheStats::new("batched"),
+            );
+            batch_stats(|| record_stats("batched".to_string(), 1, &window, stats.clone()));
+            assert!(get_stats("batched").is_none());
+        });
+        assert_eq!(get_stats("batched").map(|stats| stats.hits), Some(3));
+
+        let global = GlobalStats::new();
+        global.register_many([
+            ("a".to_string(), CacheStats::new("a")),
+            ("b".to_string(), CacheStats::new("b")),
+        ]);
+        assert_eq!(global.all().len(), 2);
+    }
+
     #[test]
     fn test_global_stats() {
         let global = GlobalStats::new();
@@ -275,6 +671,9 @@ mod tests {
             misses: 10,
             entries: 30,
             hit_rate: 0.833,
+            recent_hit_rate: None,
+            bytes: 0,
+            deduped: 0,
         };
         let stats2 = CacheStats {
             name: "cache2".to_string(),
@@ -282,6 +681,9 @@ mod tests {
             misses: 20,
             entries: 25,
             hit_rate: 0.6,
+            recent_hit_rate: None,
+            bytes: 0,
+            deduped: 0,
         };
         global.register("cache1", stats1);
         global.register("cache2", stats2);
@@ -292,6 +694,9 @@ mod tests {
         let retrieved = global.get("cache1").unwrap();
         assert_eq!(retrieved.name, "cache1");
         assert_eq!(retrieved.hits, 50);
+        let mut visited = 0;
+        global.for_each(|stats| visited += stats.entries);
+        assert_eq!(visited, 55);
     }
 
     #[test]
@@ -302,4 +707,54 @@ mod tests {
             "this_is..."
         );
     }
+
+    #[test]
+    fn test_sorted_by_hit_rate() {
+        let global = GlobalStats::new();
+        for (name, hit_rate) in [("b", 0.9), ("a", 0.2), ("c", 0.2)] {
+            let mut stats = CacheStats::new(name);
+            stats.hit_rate = hit_rate;
+            global.register(name, stats);
+        }
+        let names = |stats: Vec<CacheStats>| stats.into_iter().map(|s| s.name).collect::<Vec<_>>();
+        assert_eq!(
+            names(global.sorted(SortKey::HitRate, false)),
+            ["a", "c", "b"]
+        );
+        assert_eq!(
+            names(global.sorted(SortKey::HitRate, true)),
+            ["b", "a", "c"]
+        );
+        assert_eq!(names(global.sorted(SortKey::Name, true)), ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_csv_escapes_names() {
+        let global = GlobalStats::new();
+        let mut stats = CacheStats::new("users, \"by id\"");
+        stats.hits = 3;
+        stats.misses = 1;
+        stats.hit_rate = 0.75;
+        global.register("users", stats);
+        assert_eq!(
+            global.to_csv(),
+            "name,entries,hits,misses,hit_rate,total_requests\n\"users, \"\"by id\"\"\",0,3,1,0.7500,4\n"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_escapes_names() {
+        let global = GlobalStats::new();
+        let mut stats = CacheStats::new("users \"by id\"");
+        stats.hits = 3;
+        global.register("users", stats);
+        let value = global.to_json_value();
+        assert_eq!(value[0]["name"], "users \"by id\"");
+        assert_eq!(value[0]["total_requests"], 3);
+        let parsed: serde_json::Value = serde_json::from_str(&global.to_json()).unwrap();
+        assert_eq!(parsed, value);
+        let round_trip: CacheStats = serde_json::from_value(value[0].clone()).unwrap();
+        assert_eq!(round_trip.hits, 3);
+    }
 }
@@ -0,0 +1,141 @@
+use crate::cache::{Cache, EvictionPolicy};
+use crate::stats::CacheStats;
+use crate::window;
+use std::hash::Hash;
+
+/// Two-level cache: a small hot L1 in front of a larger L2
+///
+/// `get` checks L1, then L2, then computes. An L2 hit is promoted into L1 and
+/// a computed value is stored in both, so each tier applies its own policy
+/// (typically a tight LRU limit for L1 and a longer TTL or larger limit for L2).
+pub struct TieredCache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    l1: Cache<K, V>,
+    l2: Cache<K, V>,
+}
+
+impl<K, V> TieredCache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Creates a tiered cache with `l1_policy` for the hot tier and `l2_policy` for the warm tier
+    pub fn new(l1_policy: EvictionPolicy, l2_policy: EvictionPolicy) -> Self {
+        Self {
+            l1: Cache::with_policy(l1_policy),
+            l2: Cache::with_policy(l2_policy),
+        }
+    }
+
+    /// Retrieves a value from L1 or L2, or computes it, filling the tiers it was missing from
+    pub fn get<F>(&self, key: &K, compute: F) -> V
+    where
+        F: FnOnce() -> V,
+    {
+        self.l1.get(key, || self.l2.get(key, compute))
+    }
+
+    /// Looks up a key in either tier without computing, promoting an L2 hit into L1
+    pub fn get_if_cached(&self, key: &K) -> Option<V> {
+        if let Some(value) = self.l1.get_if_cached(key) {
+            return Some(value);
+        }
+        let value = self.l2.get_if_cached(key)?;
+        self.l1.insert(key.clone(), value.clone());
+        Some(value)
+    }
+
+    /// Inserts a value into both tiers
+    pub fn insert(&self, key: K, value: V) {
+        self.l2.insert(key.clone(), value.clone());
+        self.l1.insert(key, value);
+    }
+
+    /// Removes a key from both tiers
+    pub fn invalidate(&self, key: &K) -> bool {
+        let l1 = self.l1.invalidate(key);
+        let l2 = self.l2.invalidate(key);
+        l1 || l2
+    }
+
+    /// Clears both tiers
+    pub fn clear(&self) {
+        self.l1.clear();
+        self.l2.clear();
+    }
+
+    /// Returns the hot tier
+    pub fn l1(&self) -> &Cache<K, V> {
+        &self.l1
+    }
+
+    /// Returns the warm tier
+    pub fn l2(&self) -> &Cache<K, V> {
+        &self.l2
+    }
+
+    /// Returns stats for the hot tier
+    pub fn l1_stats(&self) -> CacheStats {
+        self.l1.stats()
+    }
+
+    /// Returns stats for the warm tier
+    pub fn l2_stats(&self) -> CacheStats {
+        self.l2.stats()
+    }
+
+    /// Returns combined stats: a hit is a request served by either tier
+    ///
+    /// Only L2 misses reach `compute`, so they are the combined misses. Entries
+    /// count L2's live entries, since values in L1 were stored in L2 as well.
+    pub fn stats(&self) -> CacheStats {
+        let l1 = self.l1.stats();
+        let l2 = self.l2.stats();
+        let mut stats = CacheStats::new("TieredCache");
+        stats.hits = l1.hits + l2.hits;
+        stats.misses = l2.misses;
+        stats.entries = l2.entries;
+        stats.bytes = l1.bytes + l2.bytes;
+        stats.deduped = l1.deduped + l2.deduped;
+        stats.hit_rate = window::rate(stats.hits, stats.misses);
+        stats
+    }
+}
+
+impl<K, V> Clone for TieredCache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            l1: self.l1.clone(),
+            l2: self.l2.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_l2_hits_are_promoted() {
+        let cache: TieredCache<u32, u32> =
+            TieredCache::new(EvictionPolicy::Lru(1), EvictionPolicy::Lru(10));
+        assert_eq!(cache.get(&1, || 10), 10);
+        assert_eq!(cache.get(&2, || 20), 20);
+        // Key 1 fell out of L1 but is still in L2
+        assert_eq!(cache.l1().peek(&1), None);
+        assert_eq!(cache.get(&1, || unreachable!()), 10);
+        assert_eq!(cache.l1().peek(&1), Some(10));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 2));
+        assert_eq!(cache.l1_stats().misses, 3);
+        assert_eq!(cache.l2_stats().hits, 1);
+    }
+}
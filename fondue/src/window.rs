@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Number of one-second buckets kept, bounding the longest window that can be queried
+const BUCKETS: usize = 300;
+
+/// Hit and miss counts for one second of cache traffic
+#[derive(Debug, Default)]
+struct Bucket {
+    second: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Ring of per-second hit/miss counters backing windowed hit rates
+///
+/// Each bucket remembers which second since creation it holds, so a bucket
+/// left over from an earlier lap of the ring is reset before being reused and
+/// ignored when summing. Buckets are atomic so recording never blocks; a
+/// count racing the reset of its bucket at the turn of a second may be lost.
+#[derive(Debug)]
+pub(crate) struct HitWindow {
+    started: Instant,
+    buckets: Box<[Bucket]>,
+}
+
+impl HitWindow {
+    /// Creates an empty window
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            buckets: (0..BUCKETS).map(|_| Bucket::default()).collect(),
+        }
+    }
+
+    /// Records `hits` and `misses` in the current second's bucket
+    pub(crate) fn record(&self, hits: u64, misses: u64) {
+        let second = self.started.elapsed().as_secs();
+        let bucket = &self.buckets[(second % BUCKETS as u64) as usize];
+        let seen = bucket.second.load(Ordering::Acquire);
+        // The first caller in a new second claims the bucket and resets it
+        if seen != second
+            && bucket
+                .second
+                .compare_exchange(seen, second, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            bucket.hits.store(0, Ordering::Relaxed);
+            bucket.misses.store(0, Ordering::Relaxed);
+        }
+        bucket.hits.fetch_add(hits, Ordering::Relaxed);
+        bucket.misses.fetch_add(misses, Ordering::Relaxed);
+    }
+
+    /// Forgets all recorded hits and misses
+    pub(crate) fn reset(&self) {
+        for bucket in self.buckets.iter() {
+            bucket.second.store(0, Ordering::Relaxed);
+            bucket.hits.store(0, Ordering::Relaxed);
+            bucket.misses.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns `(hits, misses)` seen within `window`, rounded up to whole seconds
+    ///
+    /// Windows longer than the ring are clamped to its length (five minutes).
+    pub(crate) fn counts(&self, window: Duration) -> (u64, u64) {
+        let now = self.started.elapsed().as_secs();
+        let seconds = (window.as_secs_f64().ceil() as u64).clamp(1, BUCKETS as u64);
+        self.buckets
+            .iter()
+            .filter(|bucket| {
+                let second = bucket.second.load(Ordering::Acquire);
+                second <= now && second + seconds > now
+            })
+            .fold((0, 0), |(hits, misses), bucket| {
+                (
+                    hits + bucket.hits.load(Ordering::Relaxed),
+                    misses + bucket.misses.load(Ordering::Relaxed),
+                )
+            })
+    }
+}
+
+/// Returns `hits / (hits + misses)`, or 0.0 when there were no requests
+pub(crate) fn rate(hits: u64, misses: u64) -> f64 {
+    let total = hits + misses;
+    if total == 0 {
+        0.0
+    } else {
+        hits as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_only_recent_buckets() {
+        let mut window = HitWindow::new();
+        window.record(3, 1);
+        assert_eq!(window.counts(Duration::from_secs(1)), (3, 1));
+        // Simulate traffic from a lap ago landing in the same slot
+        window.buckets[1] = Bucket {
+            second: AtomicU64::new(1 + BUCKETS as u64),
+            hits: AtomicU64::new(50),
+            misses: AtomicU64::new(50),
+        };
+        assert_eq!(window.counts(Duration::from_secs(600)), (3, 1));
+        assert_eq!(rate(3, 1), 0.75);
+        assert_eq!(rate(0, 0), 0.0);
+    }
+}
@@ -0,0 +1,16 @@
+use fondue::{cache_get, cache_get_with_limit, set_default_ttl, TtlType};
+use std::time::Duration;
+
+#[test]
+fn default_ttl_applies_to_new_unbounded_namespaces() {
+    assert_eq!(cache_get("before", "a", || 1u32), 1);
+    set_default_ttl(Some((Duration::from_millis(20), TtlType::Fixed)));
+    assert_eq!(cache_get("after", "a", || 1u32), 1);
+    assert_eq!(cache_get_with_limit("limited", "a", 10, || 1u32), 1);
+
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(cache_get("after", "a", || 2u32), 2);
+    // Namespaces that existed before, or were given a policy, are untouched
+    assert_eq!(cache_get("before", "a", || 2u32), 1);
+    assert_eq!(cache_get_with_limit("limited", "a", 10, || 2u32), 1);
+}
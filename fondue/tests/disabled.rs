@@ -0,0 +1,16 @@
+use fondue::{cache_get, cache_stats_for_namespace, set_caching_enabled};
+
+#[test]
+fn disabling_caching_bypasses_global_caches() {
+    assert_eq!(cache_get("disabled", "a", || 1u32), 1);
+
+    set_caching_enabled(false);
+    assert_eq!(cache_get("disabled", "a", || 2u32), 2);
+    assert_eq!(cache_get("disabled", "b", || 3u32), 3);
+    let stats = cache_stats_for_namespace("disabled");
+    assert_eq!((stats.hits, stats.misses, stats.entries), (0, 3, 1));
+
+    // Entries cached before disabling are served again
+    set_caching_enabled(true);
+    assert_eq!(cache_get("disabled", "a", || 4u32), 1);
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "memoize")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static FIB_CALLS: AtomicUsize = AtomicUsize::new(0);
+static LABEL_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[fondue::memoize]
+fn fib(n: u64) -> u64 {
+    FIB_CALLS.fetch_add(1, Ordering::SeqCst);
+    if n < 2 {
+        n
+    } else {
+        fib(n - 1) + fib(n - 2)
+    }
+}
+
+#[fondue::memoize(ttl = "5m", limit = 100)]
+fn label(prefix: &str, id: u32) -> String {
+    LABEL_CALLS.fetch_add(1, Ordering::SeqCst);
+    format!("{}-{}", prefix, id)
+}
+
+#[test]
+fn test_memoize_recursive_function() {
+    assert_eq!(fib(40), 102_334_155);
+    // Each n in 0..=40 is computed exactly once
+    assert_eq!(FIB_CALLS.load(Ordering::SeqCst), 41);
+    assert_eq!(fib(40), 102_334_155);
+    assert_eq!(FIB_CALLS.load(Ordering::SeqCst), 41);
+}
+
+#[test]
+fn test_memoize_with_policy_arguments() {
+    assert_eq!(label("a:b", 1), "a:b-1");
+    assert_eq!(label("a:b", 1), "a:b-1");
+    assert_eq!(label("a", 1), "a-1");
+    assert_eq!(LABEL_CALLS.load(Ordering::SeqCst), 2);
+}
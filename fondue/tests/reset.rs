@@ -0,0 +1,15 @@
+#![cfg(feature = "test-util")]
+
+use fondue::{cache_get, cache_namespaces, get_stats, reset_global_state, CacheStats};
+
+#[test]
+fn reset_global_state_clears_namespaces_and_stats() {
+    assert_eq!(cache_get("reset", "a", || 1u32), 1);
+    fondue::register_stats("reset_stats", CacheStats::new("reset_stats"));
+    assert_eq!(cache_namespaces(), ["reset"]);
+
+    reset_global_state();
+    assert!(cache_namespaces().is_empty());
+    assert!(get_stats("reset_stats").is_none());
+    assert_eq!(cache_get("reset", "a", || 2u32), 2);
+}